@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use std::fs;
+use std::path::PathBuf;
+use toml_edit::{value, Document};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+pub fn config_dir() -> Option<PathBuf> {
+    ProjectDirs::from("", "Ba-koD", "isaac_mod_manager").map(|dirs| dirs.config_dir().to_path_buf())
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+}
+
+// A missing or malformed file reads as empty so callers fall back to auto-detection.
+fn read_document() -> Document {
+    config_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| content.parse::<Document>().ok())
+        .unwrap_or_default()
+}
+
+fn write_document(document: &Document) -> Result<()> {
+    let path = config_path().context("No config directory for this user")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, document.to_string())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub fn read_string(key: &str) -> Option<String> {
+    read_document().get(key)?.as_str().map(ToOwned::to_owned)
+}
+
+pub fn write_string(key: &str, content: &str) -> Result<()> {
+    let mut document = read_document();
+    document[key] = value(content);
+    write_document(&document)
+}
+
+// Only the one-time registry migration needs to tell an unset key from a default.
+#[cfg(target_os = "windows")]
+pub fn contains(key: &str) -> bool {
+    read_document().contains_key(key)
+}
+
+pub fn read_bool(key: &str) -> Option<bool> {
+    read_document().get(key)?.as_bool()
+}
+
+pub fn write_bool(key: &str, enabled: bool) -> Result<()> {
+    let mut document = read_document();
+    document[key] = value(enabled);
+    write_document(&document)
+}
+
+pub fn read_integer(key: &str) -> Option<i64> {
+    read_document().get(key)?.as_integer()
+}
+
+pub fn write_integer(key: &str, number: i64) -> Result<()> {
+    let mut document = read_document();
+    document[key] = value(number);
+    write_document(&document)
+}
+
+pub fn remove(key: &str) -> Result<()> {
+    let mut document = read_document();
+    if document.remove(key).is_some() {
+        write_document(&document)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum PatcherError {
+    SubscriptionRequired { workshop_id: u64 },
+    LocalNewer { local: String, remote: String },
+    ModFolderUnavailable(PathBuf),
+    SteamCmd(String),
+    Network(reqwest::Error),
+    Io(std::io::Error),
+    Other(anyhow::Error),
+}
+
+impl fmt::Display for PatcherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SubscriptionRequired { workshop_id } => write!(
+                f,
+                "Steam client workshop cache was not found yet for {}. Make sure the logged-in Steam account can access this item, subscribe/download it in Steam, wait for downloads to finish, then retry.",
+                workshop_id
+            ),
+            Self::LocalNewer { local, remote } => write!(
+                f,
+                "Local version {} is newer than Steam version {}. Confirm before matching Steam version.",
+                local, remote
+            ),
+            Self::ModFolderUnavailable(path) => write!(
+                f,
+                "Mod folder became unavailable (drive disconnected?): {}",
+                path.display()
+            ),
+            Self::SteamCmd(message) => write!(f, "{}", message),
+            Self::Network(e) => write!(f, "Network error: {}", e),
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Other(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+impl std::error::Error for PatcherError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Network(e) => Some(e),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PatcherError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<reqwest::Error> for PatcherError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Network(e)
+    }
+}
+
+// Internal helpers keep using anyhow; a PatcherError raised inside them survives the trip back.
+impl From<anyhow::Error> for PatcherError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<PatcherError>() {
+            Ok(e) => e,
+            Err(e) => Self::Other(e),
+        }
+    }
+}
@@ -1,18 +1,33 @@
 use directories::UserDirs;
 use std::collections::HashSet;
+use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 #[cfg(target_os = "windows")]
 pub fn find_steam_path_from_registry() -> Option<PathBuf> {
     use winreg::enums::*;
     use winreg::RegKey;
 
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let steam = hkcu.open_subkey("Software\\Valve\\Steam").ok()?;
-    let path_str: String = steam.get_value("SteamPath").ok()?;
+    let candidates = [
+        (HKEY_CURRENT_USER, "Software\\Valve\\Steam", "SteamPath"),
+        (
+            HKEY_LOCAL_MACHINE,
+            "SOFTWARE\\WOW6432Node\\Valve\\Steam",
+            "InstallPath",
+        ),
+        (HKEY_LOCAL_MACHINE, "SOFTWARE\\Valve\\Steam", "InstallPath"),
+    ];
 
-    Some(PathBuf::from(path_str))
+    candidates
+        .into_iter()
+        .filter_map(|(hive, subkey, value)| {
+            let key = RegKey::predef(hive).open_subkey(subkey).ok()?;
+            let path_str: String = key.get_value(value).ok()?;
+            Some(PathBuf::from(path_str))
+        })
+        .find(|path| path.is_dir())
 }
 
 pub fn find_steam_from_path_env() -> Option<PathBuf> {
@@ -28,72 +43,242 @@ pub fn find_steam_from_path_env() -> Option<PathBuf> {
     None
 }
 
+const ISAAC_GAME_SUBDIR: &str = "steamapps/common/The Binding of Isaac Rebirth";
+
+fn has_isaac_executable(game_path: &Path) -> bool {
+    ["isaac-ng.exe", "isaac-ng", "isaac.x64", "isaac.i386"]
+        .iter()
+        .any(|name| game_path.join(name).is_file())
+        || has_app_bundle(game_path)
+}
+
+// The Mac build ships as an .app bundle instead of a bare executable.
+fn has_app_bundle(game_path: &Path) -> bool {
+    cfg!(target_os = "macos")
+        && fs::read_dir(game_path).is_ok_and(|entries| {
+            entries.flatten().any(|entry| {
+                entry.path().is_dir()
+                    && entry
+                        .path()
+                        .extension()
+                        .is_some_and(|extension| extension.eq_ignore_ascii_case("app"))
+            })
+        })
+}
+
+// Mac keeps mods next to the save data rather than inside the game folder.
+pub fn find_isaac_mods_path(game_path: &Path) -> PathBuf {
+    let game_mods = game_path.join("mods");
+    if !cfg!(target_os = "macos") || game_mods.is_dir() {
+        return game_mods;
+    }
+
+    UserDirs::new()
+        .map(|user_dirs| user_dirs.home_dir().join("Library/Application Support"))
+        .and_then(|support| {
+            [
+                "Binding of Isaac Afterbirth+ Mods",
+                "Binding of Isaac Rebirth/mods",
+            ]
+            .iter()
+            .map(|name| support.join(name))
+            .find(|path| path.is_dir())
+        })
+        .unwrap_or(game_mods)
+}
+
 pub fn find_isaac_game_path() -> Option<PathBuf> {
-    // 1. Try Windows Registry (Windows only)
+    find_isaac_game_paths().into_iter().next().or_else(|| {
+        // Weak match when the executable name differs (e.g. an unusual platform build)
+        candidate_game_paths()
+            .into_iter()
+            .find(|path| path.is_dir())
+    })
+}
+
+// Every install that actually contains the game executable, registry and PATH hits first.
+pub fn find_isaac_game_paths() -> Vec<PathBuf> {
+    let paths = candidate_game_paths()
+        .into_iter()
+        .filter(|path| has_isaac_executable(path))
+        .chain(find_epic_game_path())
+        .collect::<Vec<_>>();
+    dedup_existing_paths(paths)
+}
+
+// The Epic launcher lists its installs in LauncherInstalled.dat, a JSON file despite the name.
+#[cfg(target_os = "windows")]
+fn find_epic_game_path() -> Option<PathBuf> {
+    let program_data = env::var_os("PROGRAMDATA")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"));
+    let content = fs::read_to_string(
+        program_data
+            .join("Epic")
+            .join("UnrealEngineLauncher")
+            .join("LauncherInstalled.dat"),
+    )
+    .ok()?;
+    let manifest: serde_json::Value = serde_json::from_str(&content).ok()?;
+
+    manifest
+        .get("InstallationList")?
+        .as_array()?
+        .iter()
+        .filter_map(|entry| entry.get("InstallLocation")?.as_str())
+        .map(PathBuf::from)
+        .filter(|path| {
+            path.to_string_lossy()
+                .to_ascii_lowercase()
+                .contains("binding of isaac")
+        })
+        .find(|path| has_isaac_executable(path))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn find_epic_game_path() -> Option<PathBuf> {
+    None
+}
+
+fn candidate_game_paths() -> Vec<PathBuf> {
+    let mut steam_roots = Vec::new();
     #[cfg(target_os = "windows")]
-    {
-        if let Some(steam_path) = find_steam_path_from_registry() {
-            let game_path = steam_path.join("steamapps/common/The Binding of Isaac Rebirth");
-            if game_path.join("isaac-ng.exe").exists() {
-                return Some(game_path);
-            }
-        }
+    steam_roots.extend(find_steam_path_from_registry());
+    steam_roots.extend(find_steam_from_path_env());
+    steam_roots.extend(find_steam_library_roots());
+
+    steam_roots
+        .into_iter()
+        .map(|root| root.join(ISAAC_GAME_SUBDIR))
+        .collect()
+}
+
+pub fn is_valid_isaac_path(path: &Path) -> bool {
+    path.is_dir()
+        && (["isaac-ng.exe", "isaac-ng", "resources", "mods"]
+            .iter()
+            .any(|name| path.join(name).exists())
+            || has_app_bundle(path))
+}
+
+const ISAAC_SAVE_FOLDER_NAMES: [&str; 5] = [
+    "Binding of Isaac Repentance+",
+    "Binding of Isaac Repentance",
+    "Binding of Isaac Afterbirth+",
+    "Binding of Isaac Afterbirth",
+    "Binding of Isaac Rebirth",
+];
+
+pub fn isaac_savedata_candidates() -> Vec<PathBuf> {
+    let Some(user_dirs) = UserDirs::new() else {
+        return Vec::new();
+    };
+    let home = user_dirs.home_dir();
+
+    let mut bases = Vec::new();
+    if let Some(documents) = user_dirs.document_dir() {
+        bases.push((documents.join("My Games"), false));
+    }
+    if cfg!(target_os = "macos") {
+        bases.push((home.join("Library/Application Support"), false));
+    }
+    if cfg!(target_os = "linux") {
+        bases.push((home.join(".local/share"), true));
+        bases.push((
+            home.join(".steam/steam/steamapps/compatdata/250900/pfx/drive_c/users/steamuser/Documents/My Games"),
+            false,
+        ));
     }
 
-    // 2. Try PATH environment variable
-    if let Some(steam_path) = find_steam_from_path_env() {
-        let game_path = steam_path.join("steamapps/common/The Binding of Isaac Rebirth");
-        if game_path.exists() {
-            // Weak check if exe not visible in PATH lookup context
-            return Some(game_path);
-        }
+    bases
+        .iter()
+        .flat_map(|(base, lowercase)| {
+            ISAAC_SAVE_FOLDER_NAMES.iter().map(move |name| {
+                if *lowercase {
+                    base.join(name.to_ascii_lowercase())
+                } else {
+                    base.join(name)
+                }
+            })
+        })
+        .collect()
+}
+
+// The most recently played edition wins when several DLC save folders exist.
+pub fn find_isaac_savedata_path() -> Option<PathBuf> {
+    isaac_savedata_candidates()
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .max_by_key(|path| {
+            let log = path.join("log.txt");
+            fs::metadata(if log.is_file() { log } else { path.clone() })
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+}
+
+pub fn local_app_dir() -> anyhow::Result<PathBuf> {
+    if let Some(local_app_data) = env::var_os("LOCALAPPDATA") {
+        return Ok(PathBuf::from(local_app_data)
+            .join("Ba-koD")
+            .join("isaac_mod_manager"));
     }
 
-    // 3. Fallback to common Steam paths
-    let common_steam_paths = [
-        r"C:\Program Files (x86)\Steam",
-        r"C:\Steam",
-        r"D:\Steam",
-        r"E:\Steam",
-        // Common library paths
-        r"C:\SteamLibrary",
-        r"D:\SteamLibrary",
-        r"E:\SteamLibrary",
-    ];
+    Ok(env::current_dir()?.join(".isaac_mod_manager"))
+}
 
-    for p in common_steam_paths {
-        let base_path = if p.starts_with("~") {
-            if let Some(user_dirs) = UserDirs::new() {
-                let home = user_dirs.home_dir();
-                let suffix = &p[2..];
-                home.join(suffix)
-            } else {
-                PathBuf::from(p)
-            }
-        } else {
-            PathBuf::from(p)
-        };
+pub fn is_isaac_running() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        Command::new("tasklist")
+            .args(["/FI", "IMAGENAME eq isaac-ng.exe", "/NH"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .to_ascii_lowercase()
+                    .contains("isaac-ng.exe")
+            })
+            .unwrap_or(false)
+    }
 
-        if base_path.exists() {
-            let game_path = base_path.join("steamapps/common/The Binding of Isaac Rebirth");
-            // Check for game executable
-            let exe_name = if cfg!(target_os = "windows") {
-                "isaac-ng.exe"
-            } else {
-                "isaac-ng"
-            };
-            // Note: Mac might be different (Isaac-ng), Linux (isaac-ng).
-
-            if game_path.join(exe_name).exists() || game_path.exists() {
-                return Some(game_path);
-            }
-        }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Command::new("pgrep")
+            .args(["-x", "isaac-ng"])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
     }
+}
 
-    // 3. Check specific Mac save data path (standard location for mods on Mac, but game is elsewhere)
-    // Skipping Mac specific game path detection for now as user emphasized Windows.
+pub fn copy_dir_all(source: &Path, target: &Path) -> std::io::Result<()> {
+    for entry in walkdir::WalkDir::new(source).follow_links(false) {
+        let entry = entry?;
+        let Ok(relative_path) = entry.path().strip_prefix(source) else {
+            continue;
+        };
+        let target_path = target.join(relative_path);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target_path)?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &target_path)?;
+        }
+    }
+    Ok(())
+}
 
-    None
+// Renames when it can and falls back to copy-then-delete when the folders sit on different drives.
+pub fn move_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+    match fs::rename(source, target) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            copy_dir_all(source, target)?;
+            fs::remove_dir_all(source)
+        }
+        result => result,
+    }
 }
 
 pub fn find_steam_library_roots() -> Vec<PathBuf> {
@@ -116,7 +301,7 @@ pub fn find_steam_library_roots() -> Vec<PathBuf> {
 }
 
 fn common_steam_roots() -> Vec<PathBuf> {
-    vec![
+    let mut roots = vec![
         PathBuf::from(r"C:\Program Files (x86)\Steam"),
         PathBuf::from(r"C:\Steam"),
         PathBuf::from(r"D:\Steam"),
@@ -124,12 +309,42 @@ fn common_steam_roots() -> Vec<PathBuf> {
         PathBuf::from(r"C:\SteamLibrary"),
         PathBuf::from(r"D:\SteamLibrary"),
         PathBuf::from(r"E:\SteamLibrary"),
-    ]
+    ];
+
+    if cfg!(target_os = "macos") {
+        if let Some(user_dirs) = UserDirs::new() {
+            roots.push(
+                user_dirs
+                    .home_dir()
+                    .join("Library/Application Support/Steam"),
+            );
+        }
+    }
+
+    if cfg!(target_os = "linux") {
+        if let Some(user_dirs) = UserDirs::new() {
+            let home = user_dirs.home_dir();
+            roots.extend([
+                home.join(".local/share/Steam"),
+                home.join(".steam/steam"),
+                home.join(".steam/root"),
+                // Flatpak Steam, including the Steam Deck desktop-mode install
+                home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+                home.join(".var/app/com.valvesoftware.Steam/data/Steam"),
+            ]);
+        }
+    }
+
+    roots
 }
 
-fn read_libraryfolders_vdf(steam_root: &PathBuf) -> Vec<PathBuf> {
-    let path = steam_root.join("steamapps").join("libraryfolders.vdf");
-    let Ok(content) = fs::read_to_string(path) else {
+fn read_libraryfolders_vdf(steam_root: &Path) -> Vec<PathBuf> {
+    let Some(content) = [
+        steam_root.join("steamapps").join("libraryfolders.vdf"),
+        steam_root.join("config").join("libraryfolders.vdf"),
+    ]
+    .iter()
+    .find_map(|path| fs::read_to_string(path).ok()) else {
         return Vec::new();
     };
 
@@ -186,3 +401,9 @@ fn dedup_existing_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
 
     output
 }
+
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_os_string();
+    path.push(suffix);
+    PathBuf::from(path)
+}
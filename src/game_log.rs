@@ -0,0 +1,57 @@
+use crate::fs_utils::isaac_savedata_candidates;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MAX_REPORTED_ERRORS: usize = 20;
+
+pub struct GameLogReport {
+    pub log_path: PathBuf,
+    pub mentions: usize,
+    pub errors: Vec<String>,
+}
+
+// Isaac rewrites log.txt on every launch, so the newest one covers the last session.
+pub fn find_game_log() -> Option<PathBuf> {
+    isaac_savedata_candidates()
+        .into_iter()
+        .map(|path| path.join("log.txt"))
+        .filter(|path| path.is_file())
+        .max_by_key(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+}
+
+pub fn scan_game_log(log_path: &Path, mod_keys: &[&str]) -> Result<GameLogReport> {
+    let bytes = fs::read(log_path)
+        .with_context(|| format!("Failed to read game log {}", log_path.display()))?;
+    let content = String::from_utf8_lossy(&bytes);
+    let mod_keys = mod_keys
+        .iter()
+        .map(|key| key.trim().to_ascii_lowercase())
+        .filter(|key| !key.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut report = GameLogReport {
+        log_path: log_path.to_path_buf(),
+        mentions: 0,
+        errors: Vec::new(),
+    };
+    for line in content.lines() {
+        let lower = line.to_ascii_lowercase();
+        if !mod_keys.iter().any(|key| lower.contains(key)) {
+            continue;
+        }
+
+        report.mentions += 1;
+        if (lower.contains("error") || lower.contains("failed"))
+            && report.errors.len() < MAX_REPORTED_ERRORS
+        {
+            report.errors.push(line.trim().to_string());
+        }
+    }
+
+    Ok(report)
+}
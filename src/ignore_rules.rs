@@ -0,0 +1,141 @@
+use crate::patcher::{DISABLE_MARKER_FILE_NAME, MANIFEST_FILE_NAME};
+use std::fs;
+use std::path::Path;
+
+pub const IGNORE_FILE_NAME: &str = ".patcherignore";
+
+#[derive(Clone)]
+struct IgnorePattern {
+    glob: String,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct IgnoreRules {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreRules {
+    pub fn load(mod_path: &Path) -> Self {
+        fs::read_to_string(mod_path.join(IGNORE_FILE_NAME))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn from_patterns(patterns: &[String]) -> Self {
+        Self::parse(&patterns.join("\n"))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let patterns = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                let glob = line.trim_start_matches('/').replace('\\', "/");
+                (!glob.is_empty()).then_some(IgnorePattern {
+                    glob,
+                    negated,
+                    dir_only,
+                    anchored,
+                })
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn is_ignored(&self, relative_path: &Path) -> bool {
+        self.matches(relative_path, false)
+    }
+
+    // Treats the last segment as a folder too, so "saves/" also matches the saves folder itself.
+    pub fn is_ignored_dir(&self, relative_path: &Path) -> bool {
+        self.matches(relative_path, true)
+    }
+
+    fn matches(&self, relative_path: &Path, is_dir: bool) -> bool {
+        let segments = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        if !is_dir
+            && segments.len() == 1
+            && [
+                IGNORE_FILE_NAME,
+                MANIFEST_FILE_NAME,
+                DISABLE_MARKER_FILE_NAME,
+            ]
+            .contains(&segments[0].as_str())
+        {
+            return true;
+        }
+
+        // A matching parent directory ignores everything below it, as in gitignore.
+        let mut ignored = false;
+        for pattern in &self.patterns {
+            for end in 1..=segments.len() {
+                if pattern.dir_only && !is_dir && end == segments.len() {
+                    continue;
+                }
+
+                let matched = if pattern.anchored {
+                    glob_match(&pattern.glob, &segments[..end].join("/"))
+                } else {
+                    glob_match(&pattern.glob, &segments[end - 1])
+                };
+                if matched {
+                    ignored = !pattern.negated;
+                    break;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    glob_match_chars(&pattern, &text)
+}
+
+fn glob_match_chars(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = pattern[2..].strip_prefix(&['/']).unwrap_or(&pattern[2..]);
+            if rest.is_empty() {
+                return true;
+            }
+            (0..=text.len()).any(|start| {
+                (start == 0 || text[start - 1] == '/') && glob_match_chars(rest, &text[start..])
+            })
+        }
+        Some('*') => (0..=text.len())
+            .take_while(|&end| end == 0 || text[end - 1] != '/')
+            .any(|end| glob_match_chars(&pattern[1..], &text[end..])),
+        Some('?') => {
+            text.first().is_some_and(|ch| *ch != '/') && glob_match_chars(&pattern[1..], &text[1..])
+        }
+        Some(ch) => {
+            text.first()
+                .is_some_and(|text_ch| text_ch.eq_ignore_ascii_case(ch))
+                && glob_match_chars(&pattern[1..], &text[1..])
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::config_file::config_dir;
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions, TryLockError};
+
+const LOCK_FILE_NAME: &str = "instance.lock";
+
+pub struct InstanceLock {
+    file: File,
+}
+
+impl InstanceLock {
+    // The OS drops the lock when the process exits, so a crash never leaves a stale lock behind.
+    // It sits in the per-user config dir so every launch sees the same file, whatever the cwd.
+    pub fn acquire() -> Result<Option<Self>> {
+        let dir = config_dir().context("No config directory for this user")?;
+        fs::create_dir_all(&dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(LOCK_FILE_NAME))?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
@@ -0,0 +1,26 @@
+//! Isaac Mod Manager as a library: the Workshop client, the sync engine and the
+//! install detection the GUI is built on, for launchers that want to sync without it.
+
+mod config_file;
+pub mod error;
+pub mod fs_utils;
+mod game_log;
+mod gui;
+mod ignore_rules;
+mod instance_lock;
+pub mod logging;
+pub mod patcher;
+mod self_update;
+mod steam_api;
+pub mod steam_workshop;
+
+pub use error::PatcherError;
+pub use fs_utils::{find_isaac_game_path, find_isaac_game_paths, find_isaac_mods_path};
+pub use logging::{Level, LogLine};
+pub use patcher::{read_mod_metadata, CompareMode, FileChange, ModMetadata, Patcher, SyncReport};
+pub use steam_workshop::SteamWorkshopClient;
+
+pub fn run_gui() -> anyhow::Result<()> {
+    self_update::remove_replaced_binary();
+    gui::run().map_err(|e| anyhow::anyhow!("GUI Error: {}", e))
+}
@@ -1,29 +1,252 @@
+use crate::error::PatcherError;
+use crate::fs_utils::{copy_dir_all, local_app_dir, move_dir, with_suffix};
+use crate::ignore_rules::{IgnoreRules, IGNORE_FILE_NAME};
+use crate::logging::{log, Level};
 use crate::steam_workshop::SteamWorkshopClient;
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime};
 use encoding_rs::EUC_KR;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{mpsc, Arc};
+use std::thread;
 
-#[derive(Deserialize, Debug)]
-struct LocalMetadata {
-    version: Option<String>,
+const MOD_ROOT_CHECK_INTERVAL: usize = 25;
+const KEPT_BACKUPS: usize = 3;
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const PRESERVED_NEW_SUFFIX: &str = ".new";
+const STAGING_SUFFIX: &str = ".staging";
+const PARTIAL_WRITE_SUFFIX: &str = ".partial";
+const PREVIOUS_SUFFIX: &str = ".previous";
+const CASE_INSENSITIVE_FS: bool = cfg!(any(target_os = "windows", target_os = "macos"));
+pub const MAX_SYNC_JOBS: usize = 16;
+pub const MANIFEST_FILE_NAME: &str = ".patcher_manifest.json";
+// OS and archiver leftovers that are never copied from the workshop or swept from the mod.
+const JUNK_FILE_NAMES: &[&str] = &[".DS_Store", "Thumbs.db"];
+const JUNK_DIR_NAMES: &[&str] = &["__MACOSX"];
+const APPLE_DOUBLE_PREFIX: &str = "._";
+// Isaac skips any mod folder holding this file; it is player state, not mod content.
+pub const DISABLE_MARKER_FILE_NAME: &str = "disable.it";
+
+// Isaac's metadata.xml; only the Workshop id is always present once a mod is uploaded.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ModMetadata {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub directory: Option<String>,
+    pub version: Option<String>,
+    pub visibility: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+}
+
+// Every path the last sync installed, with the SHA-256 of the installed content.
+#[derive(Default, Deserialize, Serialize)]
+struct SyncManifest {
+    #[serde(default)]
+    workshop_updated: Option<u64>,
+    files: BTreeMap<String, String>,
+}
+
+impl SyncManifest {
+    fn load(mod_path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(mod_path.join(MANIFEST_FILE_NAME)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn from_source_files(
+        source_files: &[(PathBuf, PathBuf)],
+        workshop_updated: Option<u64>,
+    ) -> Result<Self> {
+        let files = source_files
+            .iter()
+            .map(|(source_path, relative_path)| {
+                Ok((manifest_key(relative_path), sha256_hex(source_path)?))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            workshop_updated,
+            files,
+        })
+    }
+
+    fn write(&self, mod_path: &Path) -> Result<()> {
+        let path = mod_path.join(MANIFEST_FILE_NAME);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub dry_run: bool,
+    pub fresh_install: bool,
+    pub new: Vec<FileChange>,
+    pub updated: Vec<FileChange>,
+    pub deleted: Vec<FileChange>,
+    pub preserved: Vec<FileChange>,
+    pub corrupted: Vec<FileChange>,
+    pub incomplete: Vec<FileChange>,
+    pub unchanged: usize,
+    pub source_files: usize,
+}
+
+impl SyncReport {
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write report {}", path.display()))
+    }
+
+    // Sections and paths are sorted so two reports can be diffed line by line.
+    pub fn write_text(&self, path: &Path) -> Result<()> {
+        let mut text = String::new();
+        for (title, changes) in [
+            ("Added", &self.new),
+            ("Changed", &self.updated),
+            ("Deleted", &self.deleted),
+            ("Kept local", &self.preserved),
+            ("Failed verification", &self.corrupted),
+            ("Missing or incomplete", &self.incomplete),
+        ] {
+            let mut changes = changes.iter().collect::<Vec<_>>();
+            changes.sort_by(|left, right| left.path.cmp(&right.path));
+            text.push_str(&format!("{} ({})\n", title, changes.len()));
+            for change in changes {
+                text.push_str(&format!(
+                    "  {} ({} bytes)\n",
+                    manifest_key(&change.path),
+                    change.size
+                ));
+            }
+            text.push('\n');
+        }
+        text.push_str(&format!("Unchanged: {}\n", self.unchanged));
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, text).with_context(|| format!("Failed to write report {}", path.display()))
+    }
+
+    pub fn applied_files(&self) -> usize {
+        (self.new.len() + self.updated.len() + self.preserved.len() + self.unchanged)
+            .saturating_sub(self.corrupted.len() + self.incomplete.len())
+    }
+
+    pub fn missing_files(&self) -> usize {
+        self.source_files.saturating_sub(self.applied_files())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.applied_files() == self.source_files
+    }
+
+    fn merge(&mut self, other: SyncReport) {
+        self.new.extend(other.new);
+        self.updated.extend(other.updated);
+        self.preserved.extend(other.preserved);
+        self.unchanged += other.unchanged;
+        for changes in [&mut self.new, &mut self.updated, &mut self.preserved] {
+            changes.sort_by(|left, right| left.path.cmp(&right.path));
+        }
+    }
+}
+
+enum WorkerMessage {
+    Log(Level, String),
+    Applied(usize),
+    Failed(anyhow::Error),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompareMode {
+    #[default]
+    Bytes,
+    Size,
+    Sha256,
+}
+
+impl CompareMode {
+    pub const ALL: [Self; 3] = [Self::Bytes, Self::Size, Self::Sha256];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bytes => "bytes",
+            Self::Size => "size",
+            Self::Sha256 => "sha256",
+        }
+    }
+}
+
+impl std::str::FromStr for CompareMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "bytes" => Ok(Self::Bytes),
+            "size" => Ok(Self::Size),
+            "sha256" | "sha" => Ok(Self::Sha256),
+            _ => Err(anyhow::anyhow!("Unknown compare mode: {}", value)),
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct Patcher {
     mod_path: PathBuf,
     allow_downgrade: bool,
     force_update: bool,
+    compact_log: bool,
+    prune: bool,
+    dry_run: bool,
+    compare_mode: CompareMode,
+    backup: bool,
+    preserve: IgnoreRules,
+    keep_on_cleanup: IgnoreRules,
+    jobs: usize,
+    staged: bool,
+    workshop_updated: Option<u64>,
+    keep_local_changes: bool,
+    verify: bool,
+    baseline: Arc<BTreeMap<String, String>>,
 }
 
 impl Patcher {
     pub fn new(_client: SteamWorkshopClient, mod_path: PathBuf) -> Self {
+        // Resolve symlinks and junctions up front so the cleanup walk compares real paths.
+        let mod_path = fs::canonicalize(&mod_path).unwrap_or(mod_path);
         Self {
             mod_path,
             allow_downgrade: false,
             force_update: false,
+            compact_log: false,
+            prune: false,
+            dry_run: false,
+            compare_mode: CompareMode::default(),
+            backup: false,
+            preserve: IgnoreRules::default(),
+            keep_on_cleanup: IgnoreRules::default(),
+            jobs: 1,
+            staged: false,
+            workshop_updated: None,
+            keep_local_changes: false,
+            verify: false,
+            baseline: Arc::default(),
         }
     }
 
@@ -37,31 +260,94 @@ impl Patcher {
         self
     }
 
+    pub fn compact_log(mut self, compact_log: bool) -> Self {
+        self.compact_log = compact_log;
+        self
+    }
+
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn compare_mode(mut self, compare_mode: CompareMode) -> Self {
+        self.compare_mode = compare_mode;
+        self
+    }
+
+    pub fn backup(mut self, backup: bool) -> Self {
+        self.backup = backup;
+        self
+    }
+
+    pub fn preserve(mut self, patterns: &[String]) -> Self {
+        self.preserve = IgnoreRules::from_patterns(patterns);
+        self
+    }
+
+    pub fn keep_on_cleanup(mut self, patterns: &[String]) -> Self {
+        self.keep_on_cleanup = IgnoreRules::from_patterns(patterns);
+        self
+    }
+
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.clamp(1, MAX_SYNC_JOBS);
+        self
+    }
+
+    pub fn staged(mut self, staged: bool) -> Self {
+        self.staged = staged;
+        self
+    }
+
+    // Recorded in the manifest so the next run can skip an unchanged download.
+    pub fn workshop_updated(mut self, workshop_updated: Option<u64>) -> Self {
+        self.workshop_updated = workshop_updated;
+        self
+    }
+
+    pub fn keep_local_changes(mut self, keep_local_changes: bool) -> Self {
+        self.keep_local_changes = keep_local_changes;
+        self
+    }
+
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
     pub fn sync_from_source_dir_with_progress<F, P>(
         &self,
         source_dir: &Path,
         logger: Option<F>,
         progress: Option<P>,
-    ) -> Result<()>
+    ) -> std::result::Result<SyncReport, PatcherError>
     where
-        F: Fn(String),
+        F: Fn(Level, String),
         P: Fn(f32, String),
     {
         self.sync_from_source_dir_with_logger_and_progress(
             source_dir,
-            logger.as_ref().map(|f| f as &dyn Fn(String)),
+            logger.as_ref().map(|f| f as &dyn Fn(Level, String)),
             progress.as_ref().map(|f| f as &dyn Fn(f32, String)),
         )
+        .map_err(PatcherError::from)
     }
 
     fn sync_from_source_dir_with_logger_and_progress(
         &self,
         source_dir: &Path,
-        logger: Option<&dyn Fn(String)>,
+        logger: Option<&dyn Fn(Level, String)>,
         progress: Option<&dyn Fn(f32, String)>,
-    ) -> Result<()> {
+    ) -> Result<SyncReport> {
         log(
             logger,
+            Level::Info,
             "Step 1/3: Checking installed version...".to_string(),
         );
         report_progress(progress, 5.0, "Checking installed version");
@@ -69,12 +355,13 @@ impl Patcher {
         self.sync_source_with_local_version(source_dir, local_version, logger, progress)
     }
 
-    fn read_local_version(&self, logger: Option<&dyn Fn(String)>) -> Option<String> {
-        let local_metadata = match read_local_metadata(&self.mod_path) {
+    fn read_local_version(&self, logger: Option<&dyn Fn(Level, String)>) -> Option<String> {
+        let local_metadata = match read_mod_metadata(&self.mod_path) {
             Ok(metadata) => metadata,
             Err(e) => {
                 log(
                     logger,
+                    Level::Warn,
                     format!("Local metadata unreadable; forcing update: {}", e),
                 );
                 None
@@ -90,28 +377,53 @@ impl Patcher {
         &self,
         workshop_path: &Path,
         local_version: Option<String>,
-        logger: Option<&dyn Fn(String)>,
+        logger: Option<&dyn Fn(Level, String)>,
         progress: Option<&dyn Fn(f32, String)>,
-    ) -> Result<()> {
+    ) -> Result<SyncReport> {
         log(
             logger,
+            Level::Info,
             "Step 3/4: Reading downloaded workshop metadata...".to_string(),
         );
         report_progress(progress, 15.0, "Reading workshop metadata");
-        let workshop_metadata = read_local_metadata(workshop_path)?;
-        let workshop_version = workshop_metadata
-            .as_ref()
-            .and_then(|metadata| normalize_version(metadata.version.as_deref()));
+        let workshop_version = match read_mod_metadata(workshop_path) {
+            Ok(Some(metadata)) => normalize_version(metadata.version.as_deref()),
+            Ok(None) => {
+                log(
+                    logger,
+                    Level::Warn,
+                    "Downloaded workshop content has no metadata.xml.".to_string(),
+                );
+                None
+            }
+            Err(e) => {
+                log(
+                    logger,
+                    Level::Warn,
+                    format!("Workshop metadata unreadable: {}", e),
+                );
+                None
+            }
+        };
 
         match (local_version.as_deref(), workshop_version.as_deref()) {
             (Some(local), Some(remote)) if local == remote && !self.force_update => {
-                log(logger, format!("Already up to date (version {}).", local));
+                log(
+                    logger,
+                    Level::Info,
+                    format!("Already up to date (version {}).", local),
+                );
+                self.record_workshop_updated(logger);
                 report_progress(progress, 100.0, "Already up to date");
-                Ok(())
+                Ok(SyncReport {
+                    dry_run: self.dry_run,
+                    ..SyncReport::default()
+                })
             }
             (Some(local), Some(remote)) if local == remote => {
                 log(
                     logger,
+                    Level::Info,
                     format!(
                         "Force update enabled: verifying all files for version {}.",
                         local
@@ -123,15 +435,24 @@ impl Patcher {
                 if !self.allow_downgrade
                     && compare_version_strings(local, remote) == Some(Ordering::Greater) =>
             {
-                Err(anyhow::anyhow!(
-                    "Local version {} is newer than Steam version {}. Confirm before matching Steam version.",
-                    local,
-                    remote
-                ))
+                Err(PatcherError::LocalNewer {
+                    local: local.to_string(),
+                    remote: remote.to_string(),
+                }
+                .into())
+            }
+            (None, Some(remote)) if is_missing_or_empty_dir(&self.mod_path) => {
+                log(
+                    logger,
+                    Level::Info,
+                    format!("Fresh install: version {}.", remote),
+                );
+                self.sync_from_dir(workshop_path, logger, progress)
             }
             (local, Some(remote)) => {
                 log(
                     logger,
+                    Level::Info,
                     format!(
                         "Update required: {} -> {}",
                         local.unwrap_or("missing"),
@@ -143,7 +464,8 @@ impl Patcher {
             (_, None) => {
                 log(
                     logger,
-                    "Workshop metadata has no version; syncing downloaded content.".to_string(),
+                    Level::Warn,
+                    "No workshop version to compare; syncing downloaded content.".to_string(),
                 );
                 self.sync_from_dir(workshop_path, logger, progress)
             }
@@ -153,91 +475,1216 @@ impl Patcher {
     fn sync_from_dir(
         &self,
         source_dir: &Path,
-        logger: Option<&dyn Fn(String)>,
+        logger: Option<&dyn Fn(Level, String)>,
         progress: Option<&dyn Fn(f32, String)>,
-    ) -> Result<()> {
-        log(
-            logger,
-            "Step 4/4: Applying downloaded files to selected mod folder...".to_string(),
-        );
-        report_progress(progress, 25.0, "Applying files");
+    ) -> Result<SyncReport> {
+        let fresh_install = is_missing_or_empty_dir(&self.mod_path);
+        if fresh_install {
+            log(
+                logger,
+                Level::Info,
+                "Step 4/4: Installing downloaded files into new mod folder...".to_string(),
+            );
+            report_progress(progress, 25.0, "Installing files");
+        } else {
+            log(
+                logger,
+                Level::Info,
+                "Step 4/4: Applying downloaded files to selected mod folder...".to_string(),
+            );
+            report_progress(progress, 25.0, "Applying files");
+        }
 
-        let mut processed_files = HashSet::new();
+        let ignore_rules = IgnoreRules::load(&self.mod_path);
+        let manifest = SyncManifest::load(&self.mod_path);
+        if !ignore_rules.is_empty() {
+            log(
+                logger,
+                Level::Info,
+                format!("Keeping files matched by {}.", IGNORE_FILE_NAME),
+            );
+        }
+        let mut source_links = Vec::new();
         let source_files = walkdir::WalkDir::new(source_dir)
             .into_iter()
             .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| {
+                entry.file_type().is_file() || (entry.path_is_symlink() && entry.depth() > 0)
+            })
             .filter_map(|entry| {
                 let source_path = entry.path().to_path_buf();
                 let relative_path = source_path.strip_prefix(source_dir).ok()?.to_path_buf();
-                (!should_skip(&relative_path)).then_some((source_path, relative_path))
+                if should_skip(&relative_path) {
+                    return None;
+                }
+                if ignore_rules.is_ignored(&relative_path) {
+                    self.log_file(logger, "Ignored", &relative_path);
+                    return None;
+                }
+                if entry.path_is_symlink() {
+                    source_links.push(relative_path);
+                    return None;
+                }
+                Some((source_path, relative_path))
             })
             .collect::<Vec<_>>();
-        let total_files = source_files.len().max(1);
+        let mut report = SyncReport {
+            dry_run: self.dry_run,
+            fresh_install,
+            source_files: source_files.len(),
+            ..SyncReport::default()
+        };
 
-        for (file_index, (source_path, relative_path)) in source_files.iter().enumerate() {
-            let target_path = self.mod_path.join(relative_path);
-            processed_files.insert(target_path.clone());
+        if self.dry_run {
+            log(
+                logger,
+                Level::Info,
+                "Dry run: no files will be changed.".to_string(),
+            );
+        } else {
+            // Only a fresh install may create the root; otherwise a vanished folder is an error.
+            if fresh_install {
+                fs::create_dir_all(&self.mod_path)
+                    .map_err(fs_error("create folder", &self.mod_path))?;
+            }
+            self.ensure_mod_root_available()?;
+            self.remove_stale_partial_writes(logger);
+        }
+
+        let backup = if self.backup && !self.dry_run && !fresh_install {
+            let backup = backup_mod_folder(&self.mod_path)?;
+            log(
+                logger,
+                Level::Info,
+                format!("Backed up mod folder to {}", backup.display()),
+            );
+            prune_backups(&self.mod_path, logger);
+            Some(backup)
+        } else {
+            None
+        };
 
-            if let Some(parent) = target_path.parent() {
-                fs::create_dir_all(parent)?;
+        let staging = if self.staged && !self.dry_run {
+            match self.prepare_staging(logger) {
+                Ok(staging) => Some(staging),
+                Err(e) => {
+                    self.roll_back(backup.as_deref(), None, logger);
+                    return Err(e);
+                }
             }
+        } else {
+            None
+        };
+        let target = Patcher {
+            mod_path: staging.clone().unwrap_or_else(|| self.mod_path.clone()),
+            baseline: Arc::new(
+                manifest
+                    .as_ref()
+                    .map(|manifest| manifest.files.clone())
+                    .unwrap_or_default(),
+            ),
+            ..self.clone()
+        };
 
-            let content = fs::read(source_path)?;
-            let is_different = fs::read(&target_path)
-                .map(|local_content| local_content != content)
-                .unwrap_or(true);
+        let mut processed_files = HashSet::new();
+        if let Err(e) = target.apply_source_files(
+            &source_files,
+            &mut processed_files,
+            &mut report,
+            logger,
+            progress,
+        ) {
+            self.roll_back(backup.as_deref(), staging.as_deref(), logger);
+            return Err(e);
+        }
+        if let Err(e) = target.apply_source_links(source_dir, &source_links, logger) {
+            log(logger, Level::Warn, format!("{:#}", e));
+        }
+        if target.verify && !self.dry_run {
+            target.verify_installed_files(&source_files, &mut report, logger, progress);
+        }
+        if !self.dry_run {
+            target.check_applied_files(&source_files, &mut report, logger);
+        }
 
-            if is_different {
-                if target_path.exists() {
-                    log(logger, format!("Updated: {}", relative_path.display()));
-                } else {
-                    log(logger, format!("New: {}", relative_path.display()));
+        if target.prune {
+            target.prune_removed_files(
+                &processed_files,
+                &ignore_rules,
+                manifest.as_ref(),
+                &mut report,
+                logger,
+                progress,
+            );
+        } else {
+            log(
+                logger,
+                Level::Info,
+                "Keeping files that are not part of the workshop content (pruning is off)."
+                    .to_string(),
+            );
+        }
+
+        if report.is_complete() && !self.dry_run {
+            if let Err(e) = SyncManifest::from_source_files(&source_files, self.workshop_updated)
+                .and_then(|manifest| manifest.write(&target.mod_path))
+            {
+                log(logger, Level::Warn, format!("{:#}", e));
+            }
+        }
+
+        if let Some(staging) = &staging {
+            if report.is_complete() {
+                if let Err(e) = self.swap_in_staging(staging, logger) {
+                    self.roll_back(backup.as_deref(), Some(staging), logger);
+                    return Err(e);
                 }
-                fs::write(&target_path, content)?;
             }
+        }
+
+        if !report.is_complete() {
+            log(
+                logger,
+                Level::Error,
+                format!(
+                    "Integrity check failed: {} of {} workshop files were not applied.",
+                    report.missing_files(),
+                    report.source_files
+                ),
+            );
+            self.roll_back(backup.as_deref(), staging.as_deref(), logger);
+            return Ok(report);
+        }
+
+        let dry_run_note = if self.dry_run {
+            " (dry run, nothing written)"
+        } else {
+            ""
+        };
+        let summary = match (fresh_install, self.dry_run) {
+            (true, false) => format!("Installed {} files.", report.new.len()),
+            (true, true) => format!(
+                "{} files would be installed{}.",
+                report.new.len(),
+                dry_run_note
+            ),
+            (false, _) => format!(
+                "{} new, {} updated, {} deleted, {} unchanged{}.",
+                report.new.len(),
+                report.updated.len(),
+                report.deleted.len(),
+                report.unchanged,
+                dry_run_note
+            ),
+        };
+        log(logger, Level::Info, summary);
+        let done = if fresh_install {
+            "Install complete"
+        } else {
+            "Update complete"
+        };
+        if self.dry_run {
+            log(
+                logger,
+                Level::Info,
+                "Dry run complete; no files were changed.".to_string(),
+            );
+        } else {
+            log(logger, Level::Info, format!("{}!", done));
+        }
+        report_progress(progress, 100.0, done);
+        Ok(report)
+    }
+
+    fn record_workshop_updated(&self, logger: Option<&dyn Fn(Level, String)>) {
+        if self.dry_run || self.workshop_updated.is_none() {
+            return;
+        }
+        let Some(mut manifest) = SyncManifest::load(&self.mod_path) else {
+            return;
+        };
+        if manifest.workshop_updated != self.workshop_updated {
+            manifest.workshop_updated = self.workshop_updated;
+            if let Err(e) = manifest.write(&self.mod_path) {
+                log(logger, Level::Warn, format!("{:#}", e));
+            }
+        }
+    }
+
+    fn apply_source_files(
+        &self,
+        source_files: &[(PathBuf, PathBuf)],
+        processed_files: &mut HashSet<PathBuf>,
+        report: &mut SyncReport,
+        logger: Option<&dyn Fn(Level, String)>,
+        progress: Option<&dyn Fn(f32, String)>,
+    ) -> Result<()> {
+        processed_files.extend(
+            source_files
+                .iter()
+                .map(|(_, relative_path)| self.mod_path.join(relative_path)),
+        );
+        let jobs = self.jobs.min(source_files.len());
+        if jobs > 1 {
+            return self.apply_source_files_parallel(source_files, jobs, report, logger, progress);
+        }
+
+        let total_files = source_files.len().max(1);
+        for (file_index, (source_path, relative_path)) in source_files.iter().enumerate() {
+            if !self.dry_run && file_index > 0 && file_index % MOD_ROOT_CHECK_INTERVAL == 0 {
+                self.ensure_mod_root_available()?;
+            }
+
+            let target_path = self.mod_path.join(relative_path);
+            self.apply_file(source_path, &target_path, relative_path, report, logger)
+                .or_else(|e| {
+                    self.ensure_mod_root_available()?;
+                    Err(e)
+                })?;
 
             let percent = 25.0 + ((file_index + 1) as f32 / total_files as f32) * 65.0;
             report_progress(
                 progress,
                 percent,
-                format!("Applying {}/{} files", file_index + 1, total_files),
+                format!(
+                    "Applying {}/{}: {}",
+                    file_index + 1,
+                    total_files,
+                    relative_path.display()
+                ),
+            );
+        }
+        Ok(())
+    }
+
+    // Links are recreated only when they stay inside the mod; anything else is skipped.
+    fn apply_source_links(
+        &self,
+        source_dir: &Path,
+        source_links: &[PathBuf],
+        logger: Option<&dyn Fn(Level, String)>,
+    ) -> Result<()> {
+        for relative_path in source_links {
+            let source_path = source_dir.join(relative_path);
+            let link_target = fs::read_link(&source_path)?;
+            let resolved = normalize_path(
+                &source_path
+                    .parent()
+                    .unwrap_or(source_dir)
+                    .join(&link_target),
             );
+            if link_target.is_absolute()
+                || !resolved.starts_with(source_dir)
+                || !resolves_inside(source_dir, &resolved)
+            {
+                log(
+                    logger,
+                    Level::Warn,
+                    format!(
+                        "Skipped symlink {}: it points outside the mod folder",
+                        relative_path.display()
+                    ),
+                );
+                continue;
+            }
+            if !cfg!(unix) {
+                log(
+                    logger,
+                    Level::Warn,
+                    format!(
+                        "Skipped symlink {}: symlinks are not recreated on this platform",
+                        relative_path.display()
+                    ),
+                );
+                continue;
+            }
+
+            let target_path = self.mod_path.join(relative_path);
+            self.ensure_inside_mod_root(relative_path, &target_path)?;
+            if fs::read_link(&target_path).is_ok_and(|existing| existing == link_target) {
+                continue;
+            }
+            if fs::symlink_metadata(&target_path).is_ok_and(|metadata| metadata.is_dir()) {
+                log(
+                    logger,
+                    Level::Warn,
+                    format!(
+                        "Skipped symlink {}: a folder with that name already exists",
+                        relative_path.display()
+                    ),
+                );
+                continue;
+            }
+
+            let action = if self.dry_run { "Would link" } else { "Linked" };
+            self.log_file(logger, action, relative_path);
+            if self.dry_run {
+                continue;
+            }
+            self.create_parent_dir(&target_path)?;
+            if fs::symlink_metadata(&target_path).is_ok() {
+                remove_file(&target_path, logger)?;
+            }
+            create_symlink(&link_target, &target_path)
+                .with_context(|| format!("Failed to create symlink {}", target_path.display()))?;
+        }
+        Ok(())
+    }
+
+    // Workers only compare and write; logging and progress stay on the calling thread.
+    fn apply_source_files_parallel(
+        &self,
+        source_files: &[(PathBuf, PathBuf)],
+        jobs: usize,
+        report: &mut SyncReport,
+        logger: Option<&dyn Fn(Level, String)>,
+        progress: Option<&dyn Fn(f32, String)>,
+    ) -> Result<()> {
+        let total_files = source_files.len().max(1);
+        let next_index = AtomicUsize::new(0);
+        let cancelled = AtomicBool::new(false);
+        let (message_tx, message_rx) = mpsc::channel();
+
+        thread::scope(|scope| {
+            let workers = (0..jobs)
+                .map(|_| {
+                    let message_tx = message_tx.clone();
+                    let next_index = &next_index;
+                    let cancelled = &cancelled;
+                    scope.spawn(move || {
+                        let mut worker_report = SyncReport::default();
+                        let worker_logger = |level: Level, message: String| {
+                            let _ = message_tx.send(WorkerMessage::Log(level, message));
+                        };
+                        while !cancelled.load(AtomicOrdering::Relaxed) {
+                            let index = next_index.fetch_add(1, AtomicOrdering::Relaxed);
+                            let Some((source_path, relative_path)) = source_files.get(index) else {
+                                break;
+                            };
+
+                            let target_path = self.mod_path.join(relative_path);
+                            let message = match self.apply_file(
+                                source_path,
+                                &target_path,
+                                relative_path,
+                                &mut worker_report,
+                                Some(&worker_logger),
+                            ) {
+                                Ok(()) => WorkerMessage::Applied(index),
+                                Err(e) => {
+                                    cancelled.store(true, AtomicOrdering::Relaxed);
+                                    WorkerMessage::Failed(e)
+                                }
+                            };
+                            let _ = message_tx.send(message);
+                        }
+                        worker_report
+                    })
+                })
+                .collect::<Vec<_>>();
+            drop(message_tx);
+
+            let mut first_error = None;
+            let mut applied = 0;
+            for message in message_rx {
+                match message {
+                    WorkerMessage::Log(level, message) => {
+                        if let Some(logger) = logger {
+                            logger(level, message);
+                        }
+                    }
+                    WorkerMessage::Applied(index) => {
+                        applied += 1;
+                        if !self.dry_run && applied % MOD_ROOT_CHECK_INTERVAL == 0 {
+                            if let Err(e) = self.ensure_mod_root_available() {
+                                cancelled.store(true, AtomicOrdering::Relaxed);
+                                first_error.get_or_insert(e);
+                            }
+                        }
+                        let percent = 25.0 + (applied as f32 / total_files as f32) * 65.0;
+                        report_progress(
+                            progress,
+                            percent,
+                            format!(
+                                "Applying {}/{}: {}",
+                                applied,
+                                total_files,
+                                source_files[index].1.display()
+                            ),
+                        );
+                    }
+                    WorkerMessage::Failed(e) => {
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+
+            for worker in workers {
+                report.merge(worker.join().unwrap_or_default());
+            }
+            match first_error {
+                Some(e) => {
+                    self.ensure_mod_root_available()?;
+                    Err(e)
+                }
+                None => Ok(()),
+            }
+        })
+    }
+
+    // Kept in the app folder beside the backups so Isaac never loads the copy as a second mod.
+    fn prepare_staging(&self, logger: Option<&dyn Fn(Level, String)>) -> Result<PathBuf> {
+        let staging = work_dir(&self.mod_path, STAGING_SUFFIX)?;
+        if staging.exists() {
+            fs::remove_dir_all(&staging)
+                .with_context(|| format!("Failed to clear {}", staging.display()))?;
+        }
+        copy_dir_all(&self.mod_path, &staging)
+            .with_context(|| format!("Failed to create staging folder {}", staging.display()))?;
+        log(
+            logger,
+            Level::Info,
+            format!("Staging update in {}", staging.display()),
+        );
+        Ok(staging)
+    }
+
+    fn swap_in_staging(
+        &self,
+        staging: &Path,
+        logger: Option<&dyn Fn(Level, String)>,
+    ) -> Result<()> {
+        let previous = work_dir(&self.mod_path, PREVIOUS_SUFFIX)?;
+        if previous.exists() {
+            fs::remove_dir_all(&previous)?;
+        }
+
+        if let Err(e) = move_dir(&self.mod_path, &previous) {
+            if previous.exists() {
+                let _ = copy_dir_all(&previous, &self.mod_path);
+                let _ = fs::remove_dir_all(&previous);
+            }
+            let _ = fs::remove_dir_all(staging);
+            return Err(anyhow::Error::from(e).context(format!(
+                "Could not swap in the update; is a file in {} open in another program?",
+                self.mod_path.display()
+            )));
+        }
+        if let Err(e) = move_dir(staging, &self.mod_path) {
+            let _ = fs::remove_dir_all(&self.mod_path);
+            let _ = move_dir(&previous, &self.mod_path);
+            let _ = fs::remove_dir_all(staging);
+            return Err(
+                anyhow::Error::from(e).context("Could not move the staged update into place")
+            );
+        }
+
+        if let Err(e) = fs::remove_dir_all(&previous) {
+            log(
+                logger,
+                Level::Warn,
+                format!("Failed to remove {}: {}", previous.display(), e),
+            );
+        }
+        log(
+            logger,
+            Level::Info,
+            "Swapped staged update into place.".to_string(),
+        );
+        Ok(())
+    }
+
+    // Every failure after the backup was taken ends here, so the mod folder is never left half-written.
+    fn roll_back(
+        &self,
+        backup: Option<&Path>,
+        staging: Option<&Path>,
+        logger: Option<&dyn Fn(Level, String)>,
+    ) {
+        if let Some(staging) = staging {
+            let _ = fs::remove_dir_all(staging);
+        }
+        if let Some(backup) = backup {
+            self.restore_backup(backup, logger);
+        }
+    }
+
+    fn restore_backup(&self, backup: &Path, logger: Option<&dyn Fn(Level, String)>) {
+        let result = fs::remove_dir_all(&self.mod_path)
+            .or_else(|e| {
+                if e.kind() == io::ErrorKind::NotFound {
+                    Ok(())
+                } else {
+                    Err(e)
+                }
+            })
+            .and_then(|()| copy_dir_all(backup, &self.mod_path));
+        match result {
+            Ok(()) => log(
+                logger,
+                Level::Warn,
+                format!("Sync failed; restored mod folder from {}", backup.display()),
+            ),
+            Err(e) => log(
+                logger,
+                Level::Error,
+                format!(
+                    "Sync failed and the backup could not be restored ({}). Copy {} back manually.",
+                    e,
+                    backup.display()
+                ),
+            ),
         }
+    }
 
+    fn prune_removed_files(
+        &self,
+        processed_files: &HashSet<PathBuf>,
+        ignore_rules: &IgnoreRules,
+        manifest: Option<&SyncManifest>,
+        report: &mut SyncReport,
+        logger: Option<&dyn Fn(Level, String)>,
+        progress: Option<&dyn Fn(f32, String)>,
+    ) {
         log(
             logger,
+            Level::Info,
             "Cleaning up files removed from workshop content...".to_string(),
         );
         report_progress(progress, 92.0, "Cleaning removed files");
+
+        // With a manifest only files a previous sync installed are candidates, so
+        // unrelated files in the mod folder survive; without one, sweep the folder.
+        let candidates = match manifest {
+            Some(manifest) => manifest
+                .files
+                .keys()
+                .map(|key| self.mod_path.join(key))
+                .filter(|path| fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_file()))
+                .collect::<Vec<_>>(),
+            None => walkdir::WalkDir::new(&self.mod_path)
+                .follow_links(false)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| entry.into_path())
+                .collect(),
+        };
+
+        let processed_by_case = if CASE_INSENSITIVE_FS {
+            processed_files
+                .iter()
+                .map(|path| (case_key(path), path))
+                .collect::<HashMap<_, _>>()
+        } else {
+            HashMap::new()
+        };
+
+        for path in candidates {
+            if processed_files.contains(&path) {
+                continue;
+            }
+
+            let Ok(relative_path) = path.strip_prefix(&self.mod_path) else {
+                continue;
+            };
+            if should_skip(relative_path)
+                || ignore_rules.is_ignored(relative_path)
+                || self.keep_on_cleanup.is_ignored(relative_path)
+                || self.is_preserved_path(relative_path)
+            {
+                continue;
+            }
+            if let Err(e) = self.ensure_inside_mod_root(relative_path, &path) {
+                log(logger, Level::Warn, format!("{:#}", e));
+                continue;
+            }
+            // On a case-insensitive volume this is the same file under its old casing.
+            if let Some(expected) = processed_by_case.get(&case_key(&path)) {
+                self.match_workshop_case(&path, expected, logger);
+                continue;
+            }
+
+            let change = FileChange {
+                path: relative_path.to_path_buf(),
+                size: fs::metadata(&path)
+                    .map(|metadata| metadata.len())
+                    .unwrap_or(0),
+            };
+            if self.dry_run {
+                self.log_file(logger, "Would delete", relative_path);
+                report.deleted.push(change);
+                continue;
+            }
+
+            self.log_file(logger, "Deleted", relative_path);
+            match remove_file(&path, logger) {
+                Ok(()) => report.deleted.push(change),
+                Err(e) => log(logger, Level::Warn, format!("{:#}", e)),
+            }
+        }
+
+        if !self.dry_run {
+            self.remove_empty_dirs(ignore_rules, logger);
+        }
+    }
+
+    fn match_workshop_case(
+        &self,
+        path: &Path,
+        expected: &Path,
+        logger: Option<&dyn Fn(Level, String)>,
+    ) {
+        let relative = |path: &Path| {
+            path.strip_prefix(&self.mod_path)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+        let message = format!("{} -> {}", relative(path), relative(expected));
+        if self.dry_run {
+            log(logger, Level::Info, format!("Would rename {}", message));
+            return;
+        }
+
+        match fs::rename(path, expected) {
+            Ok(()) => log(
+                logger,
+                Level::Info,
+                format!("Renamed {} to match workshop casing", message),
+            ),
+            Err(e) => log(
+                logger,
+                Level::Warn,
+                format!("Failed to rename {}: {}", message, e),
+            ),
+        }
+    }
+
+    fn remove_empty_dirs(
+        &self,
+        ignore_rules: &IgnoreRules,
+        logger: Option<&dyn Fn(Level, String)>,
+    ) {
         for entry in walkdir::WalkDir::new(&self.mod_path)
+            .min_depth(1)
+            .follow_links(false)
+            .contents_first(true)
             .into_iter()
             .filter_map(|entry| entry.ok())
         {
-            if !entry.file_type().is_file() {
+            if !entry.file_type().is_dir() {
                 continue;
             }
 
-            let path = entry.path().to_path_buf();
-            if processed_files.contains(&path) {
+            let path = entry.path();
+            let Ok(relative_path) = path.strip_prefix(&self.mod_path) else {
+                continue;
+            };
+            if ignore_rules.is_ignored_dir(relative_path)
+                || self.keep_on_cleanup.is_ignored_dir(relative_path)
+                || self.preserve.is_ignored_dir(relative_path)
+            {
                 continue;
             }
 
-            let Ok(relative_path) = path.strip_prefix(&self.mod_path) else {
+            let Ok(children) = fs::read_dir(path) else {
+                continue;
+            };
+            let children = children
+                .flatten()
+                .map(|child| child.path())
+                .collect::<Vec<_>>();
+            let only_junk = children.iter().all(|child| {
+                let relative_child = child.strip_prefix(&self.mod_path).unwrap_or(child);
+                fs::symlink_metadata(child).is_ok_and(|metadata| metadata.is_file())
+                    && should_skip(child)
+                    && !ignore_rules.is_ignored(relative_child)
+                    && !self.keep_on_cleanup.is_ignored(relative_child)
+                    && !self.is_preserved_path(relative_child)
+            });
+            if !only_junk {
+                continue;
+            }
+
+            let result = children
+                .iter()
+                .try_for_each(|child| remove_file(child, logger))
+                .and_then(|()| fs::remove_dir(path).map_err(fs_error("delete", path)));
+            match result {
+                Ok(()) => self.log_file(logger, "Removed empty dir", relative_path),
+                Err(e) => log(logger, Level::Warn, format!("{:#}", e)),
+            }
+        }
+    }
+
+    fn apply_file(
+        &self,
+        source_path: &Path,
+        target_path: &Path,
+        relative_path: &Path,
+        report: &mut SyncReport,
+        logger: Option<&dyn Fn(Level, String)>,
+    ) -> Result<()> {
+        self.ensure_inside_mod_root(relative_path, target_path)?;
+        let Some(content) = self.changed_content(source_path, target_path)? else {
+            report.unchanged += 1;
+            return Ok(());
+        };
+
+        let is_new = !target_path.exists();
+        if !is_new && self.preserve.is_ignored(relative_path) {
+            return self.save_preserved_copy(target_path, relative_path, &content, report, logger);
+        }
+        if !is_new && self.is_locally_modified(relative_path, target_path) {
+            if self.keep_local_changes {
+                return self.save_preserved_copy(
+                    target_path,
+                    relative_path,
+                    &content,
+                    report,
+                    logger,
+                );
+            }
+            log(
+                logger,
+                Level::Warn,
+                format!(
+                    "Local modification to {} will be overwritten",
+                    relative_path.display()
+                ),
+            );
+        }
+        let action = match (is_new, self.dry_run) {
+            (true, false) => "New",
+            (false, false) => "Updated",
+            (true, true) => "Would add",
+            (false, true) => "Would update",
+        };
+        self.log_file(logger, action, relative_path);
+        if !self.dry_run {
+            self.create_parent_dir(target_path)?;
+            write_file(target_path, &content, logger)?;
+            copy_unix_mode(source_path, target_path)?;
+        }
+        let change = FileChange {
+            path: relative_path.to_path_buf(),
+            size: content.len() as u64,
+        };
+        if is_new {
+            report.new.push(change);
+        } else {
+            report.updated.push(change);
+        }
+
+        Ok(())
+    }
+
+    // Confirms each workshop file is on disk with the source size, independent of the counters.
+    fn check_applied_files(
+        &self,
+        source_files: &[(PathBuf, PathBuf)],
+        report: &mut SyncReport,
+        logger: Option<&dyn Fn(Level, String)>,
+    ) {
+        let kept = report
+            .preserved
+            .iter()
+            .map(|change| change.path.clone())
+            .collect::<HashSet<_>>();
+        let corrupted = report
+            .corrupted
+            .iter()
+            .map(|change| change.path.clone())
+            .collect::<HashSet<_>>();
+
+        for (source_path, relative_path) in source_files {
+            if corrupted.contains(relative_path) {
                 continue;
+            }
+            let size = fs::metadata(source_path).map_or(0, |metadata| metadata.len());
+            let target = fs::metadata(self.mod_path.join(relative_path));
+            let applied = match target {
+                Ok(metadata) => {
+                    metadata.is_file() && (kept.contains(relative_path) || metadata.len() == size)
+                }
+                Err(_) => false,
             };
-            if should_skip(relative_path) {
+            if applied {
+                continue;
+            }
+
+            log(
+                logger,
+                Level::Error,
+                format!("{} is missing or incomplete", relative_path.display()),
+            );
+            report.incomplete.push(FileChange {
+                path: relative_path.clone(),
+                size,
+            });
+        }
+    }
+
+    // Hashes the files written this run against the workshop copy and rewrites mismatches once,
+    // catching writes that reported success but did not reach the disk intact.
+    fn verify_installed_files(
+        &self,
+        source_files: &[(PathBuf, PathBuf)],
+        report: &mut SyncReport,
+        logger: Option<&dyn Fn(Level, String)>,
+        progress: Option<&dyn Fn(f32, String)>,
+    ) {
+        log(
+            logger,
+            Level::Info,
+            "Verifying installed files...".to_string(),
+        );
+        report_progress(progress, 88.0, "Verifying installed files");
+        // Unchanged files were already compared this run; only re-read what was written.
+        let written = report
+            .new
+            .iter()
+            .chain(&report.updated)
+            .map(|change| change.path.clone())
+            .collect::<HashSet<_>>();
+
+        let mut repaired = 0;
+        for (source_path, relative_path) in source_files {
+            if !written.contains(relative_path) {
+                continue;
+            }
+            let target_path = self.mod_path.join(relative_path);
+            if files_match(source_path, &target_path) {
+                continue;
+            }
+
+            log(
+                logger,
+                Level::Warn,
+                format!(
+                    "{} does not match the workshop copy; writing it again",
+                    relative_path.display()
+                ),
+            );
+            let rewritten = fs::read(source_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| write_file(&target_path, &content, logger))
+                .and_then(|()| copy_unix_mode(source_path, &target_path));
+            if rewritten.is_ok() && files_match(source_path, &target_path) {
+                repaired += 1;
                 continue;
             }
 
-            log(logger, format!("Deleted: {}", relative_path.display()));
-            let _ = fs::remove_file(path);
+            log(
+                logger,
+                Level::Error,
+                format!("{} failed verification", relative_path.display()),
+            );
+            report.corrupted.push(FileChange {
+                path: relative_path.clone(),
+                size: fs::metadata(source_path).map_or(0, |metadata| metadata.len()),
+            });
         }
 
-        log(logger, "Update complete!".to_string());
-        report_progress(progress, 100.0, "Update complete");
+        log(
+            logger,
+            Level::Info,
+            format!(
+                "Verified {} files ({} rewritten, {} failed)",
+                written.len(),
+                repaired,
+                report.corrupted.len()
+            ),
+        );
+    }
+
+    // A file differs from what the last sync installed only if someone edited it since.
+    fn is_locally_modified(&self, relative_path: &Path, target_path: &Path) -> bool {
+        self.baseline
+            .get(&manifest_key(relative_path))
+            .is_some_and(|installed_hash| {
+                sha256_hex(target_path).is_ok_and(|local_hash| &local_hash != installed_hash)
+            })
+    }
+
+    fn save_preserved_copy(
+        &self,
+        target_path: &Path,
+        relative_path: &Path,
+        content: &[u8],
+        report: &mut SyncReport,
+        logger: Option<&dyn Fn(Level, String)>,
+    ) -> Result<()> {
+        let new_path = with_suffix(target_path, PRESERVED_NEW_SUFFIX);
+        log(
+            logger,
+            Level::Info,
+            format!(
+                "Kept local {} (new version saved as {})",
+                relative_path.display(),
+                with_suffix(relative_path, PRESERVED_NEW_SUFFIX).display()
+            ),
+        );
+        if !self.dry_run {
+            write_file(&new_path, content, logger)?;
+            copy_unix_mode(target_path, &new_path)?;
+        }
+        report.preserved.push(FileChange {
+            path: relative_path.to_path_buf(),
+            size: content.len() as u64,
+        });
         Ok(())
     }
+
+    // Preserved files and the .new copies written next to them are never pruned.
+    fn is_preserved_path(&self, relative_path: &Path) -> bool {
+        if self.preserve.is_empty() {
+            return false;
+        }
+
+        self.preserve.is_ignored(relative_path)
+            || relative_path
+                .to_str()
+                .and_then(|path| path.strip_suffix(PRESERVED_NEW_SUFFIX))
+                .is_some_and(|path| self.preserve.is_ignored(Path::new(path)))
+    }
+
+    // Returns the source content when the file needs writing, so it is read only once.
+    // Sizes are compared first; a size mismatch settles it in every mode.
+    fn changed_content(&self, source_path: &Path, target_path: &Path) -> Result<Option<Vec<u8>>> {
+        let size = fs::metadata(source_path)?.len();
+        let same_size = fs::metadata(target_path).is_ok_and(|local| local.len() == size);
+        if !same_size {
+            return Ok(Some(fs::read(source_path)?));
+        }
+
+        match self.compare_mode {
+            CompareMode::Size => Ok(None),
+            CompareMode::Bytes => {
+                let content = fs::read(source_path)?;
+                let same =
+                    fs::read(target_path).is_ok_and(|local_content| local_content == content);
+                Ok((!same).then_some(content))
+            }
+            CompareMode::Sha256 => {
+                let hash = sha256_file(source_path)?;
+                if sha256_file(target_path).is_ok_and(|local_hash| local_hash == hash) {
+                    Ok(None)
+                } else {
+                    Ok(Some(fs::read(source_path)?))
+                }
+            }
+        }
+    }
+
+    // Manifest entries or symlinked folders must never send a write or delete outside the mod.
+    fn ensure_inside_mod_root(&self, relative_path: &Path, target_path: &Path) -> Result<()> {
+        let lexically_inside = relative_path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        let physically_inside = || {
+            let Ok(mod_root) = fs::canonicalize(&self.mod_path) else {
+                return true;
+            };
+            target_path
+                .ancestors()
+                .skip(1)
+                .find_map(|ancestor| fs::canonicalize(ancestor).ok())
+                .is_none_or(|ancestor| ancestor.starts_with(&mod_root))
+        };
+        if lexically_inside && physically_inside() {
+            return Ok(());
+        }
+
+        Err(anyhow::anyhow!(
+            "Refusing to touch {}: it escapes the mod folder",
+            relative_path.display()
+        ))
+    }
+
+    fn ensure_mod_root_available(&self) -> Result<()> {
+        if self.mod_path.is_dir() {
+            return Ok(());
+        }
+
+        Err(PatcherError::ModFolderUnavailable(self.mod_path.clone()).into())
+    }
+
+    // Left behind when an earlier sync was killed between writing and renaming a file.
+    fn remove_stale_partial_writes(&self, logger: Option<&dyn Fn(Level, String)>) {
+        let stale = walkdir::WalkDir::new(&self.mod_path)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry.file_type().is_file()
+                    && is_partial_write(&entry.file_name().to_string_lossy())
+            });
+        for entry in stale {
+            if let Err(e) = fs::remove_file(entry.path()) {
+                log(
+                    logger,
+                    Level::Warn,
+                    format!("Failed to remove {}: {}", entry.path().display(), e),
+                );
+            }
+        }
+    }
+
+    // Checked before every create so a root that vanished mid-sync is not silently rebuilt.
+    fn create_parent_dir(&self, path: &Path) -> Result<()> {
+        self.ensure_mod_root_available()?;
+        match path.parent() {
+            Some(parent) => fs::create_dir_all(parent).map_err(fs_error("create folder", parent)),
+            None => Ok(()),
+        }
+    }
+
+    fn log_file(&self, logger: Option<&dyn Fn(Level, String)>, action: &str, relative_path: &Path) {
+        if !self.compact_log {
+            log(
+                logger,
+                Level::Info,
+                format!("{}: {}", action, relative_path.display()),
+            );
+        }
+    }
+}
+
+// Content goes to a sibling temp file first so a failed write never leaves a truncated file.
+fn write_file(path: &Path, content: &[u8], logger: Option<&dyn Fn(Level, String)>) -> Result<()> {
+    let partial_path = partial_write_path(path);
+    retry_if_readonly(path, logger, || {
+        fs::write(&partial_path, content)
+            .and_then(|()| keep_existing_mode(path, &partial_path))
+            .and_then(|()| fs::rename(&partial_path, path))
+            .inspect_err(|_| {
+                let _ = fs::remove_file(&partial_path);
+            })
+    })
+    .map_err(fs_error("write", path))
+}
+
+// Hidden and per-process, so a crashed write never looks like mod content or collides with another sync.
+fn partial_write_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(
+        ".{}.{}{}",
+        file_name,
+        std::process::id(),
+        PARTIAL_WRITE_SUFFIX
+    ))
+}
+
+fn is_partial_write(file_name: &str) -> bool {
+    file_name
+        .strip_prefix('.')
+        .and_then(|name| name.strip_suffix(PARTIAL_WRITE_SUFFIX))
+        .and_then(|name| name.rsplit_once('.'))
+        .is_some_and(|(name, pid)| {
+            !name.is_empty() && !pid.is_empty() && pid.bytes().all(|byte| byte.is_ascii_digit())
+        })
+}
+
+// The rename replaces the inode, so carry the old file's mode over to the new one.
+#[cfg(unix)]
+fn keep_existing_mode(path: &Path, partial_path: &Path) -> io::Result<()> {
+    match fs::metadata(path) {
+        Ok(metadata) => fs::set_permissions(partial_path, metadata.permissions()),
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn keep_existing_mode(_path: &Path, _partial_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+// Workshop content can ship executable helper scripts; keep their mode bits on Unix.
+#[cfg(unix)]
+fn copy_unix_mode(source_path: &Path, target_path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = (fs::metadata(source_path)?.permissions().mode() & 0o777) | 0o600;
+    fs::set_permissions(target_path, fs::Permissions::from_mode(mode))
+        .map_err(fs_error("set permissions on", target_path))
+}
+
+#[cfg(not(unix))]
+fn copy_unix_mode(_source_path: &Path, _target_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn remove_file(path: &Path, logger: Option<&dyn Fn(Level, String)>) -> Result<()> {
+    retry_if_readonly(path, logger, || fs::remove_file(path)).map_err(fs_error("delete", path))
+}
+
+fn retry_if_readonly(
+    path: &Path,
+    logger: Option<&dyn Fn(Level, String)>,
+    operation: impl Fn() -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    match operation() {
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied && clear_readonly(path)? => {
+            log(
+                logger,
+                Level::Warn,
+                format!("Cleared read-only attribute: {}", path.display()),
+            );
+            operation()
+        }
+        result => result,
+    }
+}
+
+fn clear_readonly(path: &Path) -> std::io::Result<bool> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    if !permissions.readonly() {
+        return Ok(false);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+
+    fs::set_permissions(path, permissions)?;
+    Ok(true)
+}
+
+// Windows reports a file held open by the game as a sharing violation, not access denied.
+const WINDOWS_SHARING_VIOLATION: i32 = 32;
+
+fn fs_error<'a>(action: &'a str, path: &'a Path) -> impl FnOnce(io::Error) -> anyhow::Error + 'a {
+    move |error| {
+        let hint = permission_hint(action, path, &error);
+        anyhow::Error::from(error).context(hint)
+    }
+}
+
+fn permission_hint(action: &str, path: &Path, error: &io::Error) -> String {
+    let hint = if cfg!(windows) && error.raw_os_error() == Some(WINDOWS_SHARING_VIOLATION) {
+        "The file is in use; close the game and any program using the mod folder."
+    } else if error.kind() == io::ErrorKind::PermissionDenied {
+        "The mod folder is not writable; check its permissions or run the manager as administrator."
+    } else {
+        return format!("Failed to {} {}", action, path.display());
+    };
+    format!("Failed to {} {}. {}", action, path.display(), hint)
+}
+
+// Follows any symlinks along the way; a dangling tail is judged by its deepest existing ancestor.
+fn resolves_inside(root: &Path, path: &Path) -> bool {
+    let Ok(root) = fs::canonicalize(root) else {
+        return false;
+    };
+    path.ancestors()
+        .find_map(|ancestor| fs::canonicalize(ancestor).ok())
+        .is_some_and(|resolved| resolved.starts_with(&root))
 }
 
 fn should_skip(relative_path: &Path) -> bool {
@@ -246,14 +1693,133 @@ fn should_skip(relative_path: &Path) -> bool {
         .unwrap_or_default()
         .to_string_lossy();
 
-    file_name == ".DS_Store" || file_name == "Thumbs.db"
+    JUNK_FILE_NAMES.contains(&file_name.as_ref())
+        || file_name.starts_with(APPLE_DOUBLE_PREFIX)
+        || is_partial_write(&file_name)
+        || relative_path.components().any(|component| {
+            JUNK_DIR_NAMES.contains(&component.as_os_str().to_string_lossy().as_ref())
+        })
+}
+
+pub fn synced_workshop_updated(mod_path: &Path) -> Option<u64> {
+    SyncManifest::load(mod_path)?.workshop_updated
+}
+
+pub fn backup_mod_folder(mod_path: &Path) -> Result<PathBuf> {
+    let backup = local_app_dir()?.join("backups").join(format!(
+        "{}_{}",
+        backup_prefix(mod_path),
+        Local::now().format(BACKUP_TIMESTAMP_FORMAT)
+    ));
+    copy_dir_all(mod_path, &backup)
+        .with_context(|| format!("Failed to back up {}", mod_path.display()))?;
+    Ok(backup)
+}
+
+fn work_dir(mod_path: &Path, suffix: &str) -> Result<PathBuf> {
+    let root = local_app_dir()?.join("staging");
+    fs::create_dir_all(&root).map_err(fs_error("create folder", &root))?;
+    Ok(root.join(format!("{}{}", backup_prefix(mod_path), suffix)))
+}
+
+fn backup_prefix(mod_path: &Path) -> String {
+    mod_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "mod".to_string())
+}
+
+fn prune_backups(mod_path: &Path, logger: Option<&dyn Fn(Level, String)>) {
+    let Ok(backup_root) = local_app_dir().map(|dir| dir.join("backups")) else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&backup_root) else {
+        return;
+    };
+
+    let prefix = format!("{}_", backup_prefix(mod_path));
+    let mut backups = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let timestamp = name.strip_prefix(&prefix)?;
+            let time = NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT).ok()?;
+            Some((time, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    backups.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+
+    for (_, path) in backups.into_iter().skip(KEPT_BACKUPS) {
+        if let Err(e) = fs::remove_dir_all(&path) {
+            log(
+                logger,
+                Level::Warn,
+                format!("Failed to remove old backup {}: {}", path.display(), e),
+            );
+        }
+    }
 }
 
-fn log(logger: Option<&dyn Fn(String)>, msg: String) {
-    if let Some(f) = logger {
-        f(msg.clone());
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
     }
-    println!("{}", msg);
+    normalized
+}
+
+#[cfg(unix)]
+fn create_symlink(link_target: &Path, path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(link_target, path)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_link_target: &Path, _path: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+fn case_key(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+fn manifest_key(relative_path: &Path) -> String {
+    relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn sha256_file(path: &Path) -> io::Result<Vec<u8>> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().to_vec())
+}
+
+fn files_match(left: &Path, right: &Path) -> bool {
+    match (sha256_file(left), sha256_file(right)) {
+        (Ok(left), Ok(right)) => left == right,
+        _ => false,
+    }
+}
+
+pub(crate) fn sha256_hex(path: &Path) -> io::Result<String> {
+    Ok(sha256_file(path)?
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+fn is_missing_or_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(true)
 }
 
 fn report_progress(
@@ -266,7 +1832,7 @@ fn report_progress(
     }
 }
 
-fn read_local_metadata(root: &Path) -> Result<Option<LocalMetadata>> {
+pub fn read_mod_metadata(root: &Path) -> Result<Option<ModMetadata>> {
     let metadata_path = root.join("metadata.xml");
     if !metadata_path.exists() {
         return Ok(None);
@@ -299,7 +1865,7 @@ fn normalize_version(version: Option<&str>) -> Option<String> {
         .map(ToOwned::to_owned)
 }
 
-fn compare_version_strings(left: &str, right: &str) -> Option<Ordering> {
+pub fn compare_version_strings(left: &str, right: &str) -> Option<Ordering> {
     if left.trim() == right.trim() {
         return Some(Ordering::Equal);
     }
@@ -346,3 +1912,81 @@ fn numeric_version_parts(version: &str) -> Vec<u64> {
 
     parts
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patcher(mod_path: &Path) -> Patcher {
+        Patcher::new(SteamWorkshopClient::new(0, 0), mod_path.to_path_buf())
+    }
+
+    #[test]
+    fn ensure_inside_mod_root_accepts_nested_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let patcher = patcher(dir.path());
+        let relative_path = Path::new("resources/gfx/item.png");
+        let target_path = patcher.mod_path.join(relative_path);
+        assert!(patcher
+            .ensure_inside_mod_root(relative_path, &target_path)
+            .is_ok());
+    }
+
+    #[test]
+    fn ensure_inside_mod_root_rejects_parent_components() {
+        let dir = tempfile::tempdir().unwrap();
+        let patcher = patcher(dir.path());
+        let relative_path = Path::new("resources/../../outside.txt");
+        let target_path = patcher.mod_path.join(relative_path);
+        assert!(patcher
+            .ensure_inside_mod_root(relative_path, &target_path)
+            .is_err());
+    }
+
+    #[test]
+    fn ensure_inside_mod_root_rejects_absolute_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let patcher = patcher(dir.path());
+        let relative_path = std::env::temp_dir().join("outside.txt");
+        let target_path = patcher.mod_path.join(&relative_path);
+        assert!(patcher
+            .ensure_inside_mod_root(&relative_path, &target_path)
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ensure_inside_mod_root_rejects_symlinked_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let patcher = patcher(dir.path());
+        std::os::unix::fs::symlink(outside.path(), patcher.mod_path.join("resources")).unwrap();
+        let relative_path = Path::new("resources/item.png");
+        let target_path = patcher.mod_path.join(relative_path);
+        assert!(patcher
+            .ensure_inside_mod_root(relative_path, &target_path)
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sync_finishes_with_looping_symlinks() {
+        let source = tempfile::tempdir().unwrap();
+        let mod_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(source.path().join("resources")).unwrap();
+        fs::write(source.path().join("resources/item.txt"), b"item").unwrap();
+        std::os::unix::fs::symlink("..", source.path().join("resources/loop")).unwrap();
+        std::os::unix::fs::symlink(".", mod_dir.path().join("loop")).unwrap();
+
+        let report = patcher(mod_dir.path())
+            .prune(true)
+            .sync_from_dir(source.path(), None, None)
+            .unwrap();
+
+        assert!(report.is_complete());
+        assert_eq!(
+            fs::read(mod_dir.path().join("resources/item.txt")).unwrap(),
+            b"item"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::fs_utils::with_suffix;
+use crate::patcher::{compare_version_strings, sha256_hex};
+use crate::steam_api::{describe_request_error, http_client};
+use anyhow::{anyhow, Context, Result};
+use reqwest::header::ACCEPT;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/Ba-koD/cb_patcher/releases?per_page=30";
+const RELEASE_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+const BINARY_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+const NEW_BINARY_SUFFIX: &str = ".new";
+const OLD_BINARY_SUFFIX: &str = ".old";
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+    size: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct AppRelease {
+    pub version: String,
+    download_url: String,
+    size: u64,
+    checksum_url: String,
+}
+
+pub fn current_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+// The rolling "latest" build is re-tagged on every push, so only v<major>.<minor>.<patch>
+// releases are considered.
+pub fn check_for_app_update() -> Result<Option<AppRelease>> {
+    let releases: Vec<GithubRelease> = http_client()?
+        .get(RELEASES_URL)
+        .header(ACCEPT, "application/vnd.github+json")
+        .timeout(RELEASE_CHECK_TIMEOUT)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(describe_request_error)?
+        .json()
+        .context("Failed to parse the release list")?;
+
+    let (version, release) = releases
+        .into_iter()
+        .filter(|release| !release.draft && !release.prerelease)
+        .filter_map(|release| Some((release_version(&release.tag_name)?, release)))
+        .max_by(|(left, _), (right, _)| {
+            compare_version_strings(left, right).unwrap_or(Ordering::Equal)
+        })
+        .context("No versioned release (vX.Y.Z) has been published")?;
+
+    match compare_version_strings(&version, current_version()) {
+        Some(Ordering::Greater) => {}
+        Some(_) => return Ok(None),
+        None => {
+            return Err(anyhow!(
+                "Cannot compare release {} with the running version {}",
+                release.tag_name,
+                current_version()
+            ))
+        }
+    }
+
+    let asset = release
+        .assets
+        .iter()
+        .find(|asset| is_platform_binary(&asset.name))
+        .ok_or_else(|| {
+            anyhow!(
+                "Release {} has no download for {}",
+                release.tag_name,
+                env::consts::OS
+            )
+        })?;
+    let checksum_name = format!("{}.sha256", asset.name);
+    let checksum = release
+        .assets
+        .iter()
+        .find(|candidate| candidate.name.eq_ignore_ascii_case(&checksum_name))
+        .ok_or_else(|| {
+            anyhow!(
+                "Release {} does not publish {}",
+                release.tag_name,
+                checksum_name
+            )
+        })?;
+    Ok(Some(AppRelease {
+        version,
+        download_url: asset.browser_download_url.clone(),
+        size: asset.size,
+        checksum_url: checksum.browser_download_url.clone(),
+    }))
+}
+
+fn release_version(tag: &str) -> Option<String> {
+    let version = tag.trim().strip_prefix('v')?;
+    let parts = version.split('.').collect::<Vec<_>>();
+    (parts.len() >= 2
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit())))
+    .then(|| version.to_string())
+}
+
+fn is_platform_binary(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    if [".zip", ".dmg", ".tar.gz", ".sha256"]
+        .iter()
+        .any(|extension| name.ends_with(extension))
+    {
+        return false;
+    }
+
+    match env::consts::OS {
+        "windows" => name.ends_with(".exe"),
+        "macos" => name.contains("mac") || name.contains("darwin"),
+        os => name.contains(os),
+    }
+}
+
+// Windows will not overwrite a running exe but does allow renaming it, so the old
+// binary is moved aside and removed on the next launch.
+pub fn install_app_update(release: &AppRelease) -> Result<PathBuf> {
+    let exe = env::current_exe().context("Failed to locate the running executable")?;
+    let new_path = with_suffix(&exe, NEW_BINARY_SUFFIX);
+    let old_path = with_suffix(&exe, OLD_BINARY_SUFFIX);
+
+    let expected_sha256 = fetch_published_checksum(&release.checksum_url)?;
+    let mut response = http_client()?
+        .get(&release.download_url)
+        .timeout(BINARY_DOWNLOAD_TIMEOUT)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(describe_request_error)?;
+    // Streamed straight to disk so the whole binary is never held in memory.
+    let downloaded = fs::File::create(&new_path)
+        .and_then(|mut file| io::copy(&mut response, &mut file))
+        .with_context(|| format!("Failed to download the update to {}", new_path.display()))
+        .and_then(|written| verify_download(&new_path, written, release, &expected_sha256));
+    if let Err(e) = downloaded {
+        let _ = fs::remove_file(&new_path);
+        return Err(e);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&new_path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    let _ = fs::remove_file(&old_path);
+    if let Err(e) = fs::rename(&exe, &old_path) {
+        let _ = fs::remove_file(&new_path);
+        return Err(anyhow::Error::from(e).context(format!(
+            "Failed to move {} aside; is the folder writable?",
+            exe.display()
+        )));
+    }
+    if let Err(e) = fs::rename(&new_path, &exe) {
+        let _ = fs::rename(&old_path, &exe);
+        let _ = fs::remove_file(&new_path);
+        return Err(anyhow::Error::from(e).context("Failed to move the new version into place"));
+    }
+    Ok(exe)
+}
+
+fn fetch_published_checksum(url: &str) -> Result<String> {
+    let content = http_client()?
+        .get(url)
+        .timeout(RELEASE_CHECK_TIMEOUT)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(describe_request_error)?;
+    // sha256sum output: "<hex>  <file name>".
+    let checksum = content
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if checksum.len() != 64 || !checksum.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(anyhow!("Published checksum for the update is malformed"));
+    }
+    Ok(checksum)
+}
+
+// A truncated download or an HTML error page must never replace the running binary.
+fn verify_download(
+    path: &Path,
+    written: u64,
+    release: &AppRelease,
+    expected_sha256: &str,
+) -> Result<()> {
+    if written != release.size {
+        return Err(anyhow!(
+            "Downloaded update for {} is {} bytes, expected {}",
+            release.version,
+            written,
+            release.size
+        ));
+    }
+    let actual = sha256_hex(path)?;
+    if actual != expected_sha256 {
+        return Err(anyhow!(
+            "Downloaded update for {} failed its SHA-256 check",
+            release.version
+        ));
+    }
+    Ok(())
+}
+
+pub fn remove_replaced_binary() {
+    if let Ok(exe) = env::current_exe() {
+        let _ = fs::remove_file(with_suffix(&exe, OLD_BINARY_SUFFIX));
+    }
+}
@@ -1,9 +1,17 @@
+use crate::error::PatcherError;
+use crate::fs_utils::local_app_dir;
+use crate::logging::{log, Level};
+use crate::steam_api::{describe_request_error, http_client, request_timeout, send_with_retry};
 use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
 use encoding_rs::EUC_KR;
 use reqwest::blocking::Client;
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
 use std::env;
 use std::fs;
-use std::io::{BufRead, BufReader, Cursor, Read};
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
 use std::sync::{mpsc, Arc, Mutex};
@@ -15,6 +23,10 @@ pub const ISAAC_APP_ID: u32 = 250900;
 pub const CONCH_BLESSING_WORKSHOP_ID: u64 = 3545334858;
 
 const STEAMCMD_ZIP_URL: &str = "https://steamcdn-a.akamaihd.net/client/installer/steamcmd.zip";
+const STEAMCMD_ZIP_MIRRORS_ENV: &str = "STEAMCMD_ZIP_MIRRORS";
+const STEAMCMD_ZIP_SHA256_ENV: &str = "STEAMCMD_ZIP_SHA256";
+const STALE_DOWNLOAD_AGE: Duration = Duration::from_secs(60 * 60);
+const ZIP_RESUME_ATTEMPTS: u32 = 3;
 const DEFAULT_STEAM_CLIENT_DOWNLOAD_WAIT: Duration = Duration::from_secs(20);
 const STEAM_CLIENT_DOWNLOAD_POLL: Duration = Duration::from_secs(2);
 
@@ -26,6 +38,7 @@ pub struct SteamWorkshopClient {
     steam_client_download_wait: Duration,
     steamcmd_lock: Option<Arc<Mutex<()>>>,
     force_download: bool,
+    offline: bool,
 }
 
 impl SteamWorkshopClient {
@@ -37,6 +50,7 @@ impl SteamWorkshopClient {
             steam_client_download_wait: DEFAULT_STEAM_CLIENT_DOWNLOAD_WAIT,
             steamcmd_lock: None,
             force_download: false,
+            offline: false,
         }
     }
 
@@ -60,7 +74,20 @@ impl SteamWorkshopClient {
         self
     }
 
-    pub fn download_latest(&self, logger: Option<&dyn Fn(String)>) -> Result<PathBuf> {
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn download_latest(
+        &self,
+        logger: Option<&dyn Fn(Level, String)>,
+    ) -> std::result::Result<PathBuf, PatcherError> {
+        self.download_latest_inner(logger)
+            .map_err(PatcherError::from)
+    }
+
+    fn download_latest_inner(&self, logger: Option<&dyn Fn(Level, String)>) -> Result<PathBuf> {
         if let Some(path) =
             find_cached_workshop_item(self.app_id, self.workshop_id, &self.steam_library_roots)
         {
@@ -69,12 +96,21 @@ impl SteamWorkshopClient {
             } else {
                 "Using Steam client workshop cache"
             };
-            log(logger, format!("{}: {}", action, path.to_string_lossy()));
+            log(
+                logger,
+                Level::Info,
+                format!("{}: {}", action, path.to_string_lossy()),
+            );
             return Ok(path);
         }
 
+        if self.offline {
+            return self.find_previous_steamcmd_download(logger);
+        }
+
         log(
             logger,
+            Level::Info,
             "Trying SteamCMD anonymous workshop download...".to_string(),
         );
         let anonymous_failed = {
@@ -82,7 +118,11 @@ impl SteamWorkshopClient {
                 .steamcmd_lock
                 .as_ref()
                 .map(|lock| {
-                    log(logger, "Waiting for SteamCMD slot...".to_string());
+                    log(
+                        logger,
+                        Level::Info,
+                        "Waiting for SteamCMD slot...".to_string(),
+                    );
                     lock.lock()
                 })
                 .transpose()
@@ -104,25 +144,22 @@ impl SteamWorkshopClient {
             {
                 true
             } else {
-                let content_dir = steamcmd_dir
-                    .join("steamapps")
-                    .join("workshop")
-                    .join("content")
-                    .join(app_id)
-                    .join(workshop_id);
+                let content_dir = steamcmd_content_dir(steamcmd_dir, self.app_id, self.workshop_id);
 
                 if content_dir.exists() {
                     log(
                         logger,
+                        Level::Info,
                         format!("Steam workshop content ready: {}", content_dir.display()),
                     );
                     return Ok(content_dir);
                 }
 
-                return Err(anyhow::anyhow!(
+                return Err(PatcherError::SteamCmd(format!(
                     "SteamCMD finished but workshop content was not found at {}",
                     content_dir.display()
-                ));
+                ))
+                .into());
             }
         };
 
@@ -135,6 +172,7 @@ impl SteamWorkshopClient {
         {
             log(
                 logger,
+                Level::Warn,
                 format!(
                     "SteamCMD failed, but Steam client workshop cache is available: {}",
                     path.display()
@@ -145,11 +183,13 @@ impl SteamWorkshopClient {
 
         log(
             logger,
+            Level::Warn,
             "SteamCMD anonymous download failed. Opening the Workshop page in the logged-in Steam client...".to_string(),
         );
         open_workshop_page(self.workshop_id, logger)?;
         log(
             logger,
+            Level::Info,
             "Waiting for Steam client workshop cache. If the item is already subscribed, wait for Steam downloads to finish.".to_string(),
         );
         if let Some(path) = wait_for_steam_client_cache(
@@ -162,33 +202,83 @@ impl SteamWorkshopClient {
             return Ok(path);
         }
 
-        log(logger, format!("SUBSCRIBE_REQUIRED:{}", self.workshop_id));
-        Err(anyhow::anyhow!(
-            "Steam client workshop cache was not found yet. Make sure the logged-in Steam account can access this item, subscribe/download it in Steam, wait for downloads to finish, then retry."
-        ))
+        Err(PatcherError::SubscriptionRequired {
+            workshop_id: self.workshop_id,
+        }
+        .into())
     }
 
-    fn steamcmd_args(&self, app_id: &str, workshop_id: &str) -> Result<Vec<String>> {
-        let mut args = Vec::new();
+    fn find_previous_steamcmd_download(
+        &self,
+        logger: Option<&dyn Fn(Level, String)>,
+    ) -> Result<PathBuf> {
+        let content_dir = find_steamcmd()
+            .and_then(|steamcmd| {
+                let steamcmd_dir = steamcmd.parent()?;
+                Some(steamcmd_content_dir(
+                    steamcmd_dir,
+                    self.app_id,
+                    self.workshop_id,
+                ))
+            })
+            .filter(|path| is_usable_workshop_dir(path))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Offline mode: no downloaded copy of workshop item {} is available",
+                    self.workshop_id
+                )
+            })?;
+
+        let downloaded_at = fs::metadata(&content_dir)
+            .and_then(|metadata| metadata.modified())
+            .map(|time| {
+                DateTime::<Local>::from(time)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| "unknown time".to_string());
+        log(
+            logger,
+            Level::Warn,
+            format!(
+                "Offline mode: using cached workshop content from {}: {}",
+                downloaded_at,
+                content_dir.display()
+            ),
+        );
+        Ok(content_dir)
+    }
 
-        args.push("+login".to_string());
-        args.push("anonymous".to_string());
-        args.push("+workshop_download_item".to_string());
-        args.push(app_id.to_string());
-        args.push(workshop_id.to_string());
-        args.push("validate".to_string());
-        args.push("+quit".to_string());
+    fn steamcmd_args(&self, app_id: &str, workshop_id: &str) -> Result<Vec<String>> {
+        let args = vec![
+            "+login".to_string(),
+            "anonymous".to_string(),
+            "+workshop_download_item".to_string(),
+            app_id.to_string(),
+            workshop_id.to_string(),
+            "validate".to_string(),
+            "+quit".to_string(),
+        ];
 
         Ok(args)
     }
 }
 
+fn steamcmd_content_dir(steamcmd_dir: &Path, app_id: u32, workshop_id: u64) -> PathBuf {
+    steamcmd_dir
+        .join("steamapps")
+        .join("workshop")
+        .join("content")
+        .join(app_id.to_string())
+        .join(workshop_id.to_string())
+}
+
 fn wait_for_steam_client_cache(
     app_id: u32,
     workshop_id: u64,
     steam_library_roots: &[PathBuf],
     wait: Duration,
-    logger: Option<&dyn Fn(String)>,
+    logger: Option<&dyn Fn(Level, String)>,
 ) -> Option<PathBuf> {
     if wait.is_zero() {
         return find_cached_workshop_item(app_id, workshop_id, steam_library_roots);
@@ -201,6 +291,7 @@ fn wait_for_steam_client_cache(
         if let Some(path) = find_cached_workshop_item(app_id, workshop_id, steam_library_roots) {
             log(
                 logger,
+                Level::Info,
                 format!("Steam client workshop cache is ready: {}", path.display()),
             );
             return Some(path);
@@ -214,6 +305,7 @@ fn wait_for_steam_client_cache(
         if elapsed >= next_log {
             log(
                 logger,
+                Level::Info,
                 format!(
                     "Waiting briefly for Steam client download... {}s remaining",
                     wait.saturating_sub(elapsed).as_secs()
@@ -237,7 +329,7 @@ fn steam_open_url(web_url: &str) -> String {
     format!("steam://openurl/{}", web_url)
 }
 
-fn open_workshop_page(workshop_id: u64, logger: Option<&dyn Fn(String)>) -> Result<()> {
+fn open_workshop_page(workshop_id: u64, logger: Option<&dyn Fn(Level, String)>) -> Result<()> {
     let web_url = workshop_public_url(workshop_id);
     let steam_url = steam_open_url(&web_url);
 
@@ -246,20 +338,32 @@ fn open_workshop_page(workshop_id: u64, logger: Option<&dyn Fn(String)>) -> Resu
         if let Some(steam_dir) = crate::fs_utils::find_steam_path_from_registry() {
             let steam_exe = steam_dir.join("steam.exe");
             if steam_exe.exists() {
-                log(logger, format!("Opening Workshop in Steam: {}", web_url));
+                log(
+                    logger,
+                    Level::Info,
+                    format!("Opening Workshop in Steam: {}", web_url),
+                );
                 Command::new(steam_exe).arg(&steam_url).spawn()?;
                 return Ok(());
             }
         }
 
-        log(logger, format!("Opening Workshop in browser: {}", web_url));
+        log(
+            logger,
+            Level::Info,
+            format!("Opening Workshop in browser: {}", web_url),
+        );
         Command::new("explorer").arg(web_url).spawn()?;
         return Ok(());
     }
 
     #[cfg(target_os = "macos")]
     {
-        log(logger, format!("Opening Workshop in Steam: {}", web_url));
+        log(
+            logger,
+            Level::Info,
+            format!("Opening Workshop in Steam: {}", web_url),
+        );
         let opened_steam = Command::new("open")
             .arg(&steam_url)
             .status()
@@ -273,7 +377,11 @@ fn open_workshop_page(workshop_id: u64, logger: Option<&dyn Fn(String)>) -> Resu
 
     #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
     {
-        log(logger, format!("Opening Workshop in Steam: {}", web_url));
+        log(
+            logger,
+            Level::Info,
+            format!("Opening Workshop in Steam: {}", web_url),
+        );
         let opened_steam = Command::new("xdg-open")
             .arg(&steam_url)
             .status()
@@ -282,7 +390,7 @@ fn open_workshop_page(workshop_id: u64, logger: Option<&dyn Fn(String)>) -> Resu
         if !opened_steam {
             Command::new("xdg-open").arg(web_url).spawn()?;
         }
-        return Ok(());
+        Ok(())
     }
 }
 
@@ -321,8 +429,13 @@ fn run_steamcmd_streaming(
     steamcmd: &Path,
     steamcmd_dir: &Path,
     args: Vec<String>,
-    logger: Option<&dyn Fn(String)>,
+    logger: Option<&dyn Fn(Level, String)>,
 ) -> Result<String> {
+    log(
+        logger,
+        Level::Debug,
+        format!("Running {} {}", steamcmd.display(), args.join(" ")),
+    );
     let mut command = Command::new(steamcmd);
     command
         .current_dir(steamcmd_dir)
@@ -360,13 +473,14 @@ fn run_steamcmd_streaming(
     }
 
     if !status.success() {
-        return Err(anyhow::anyhow!(
+        return Err(PatcherError::SteamCmd(format!(
             "SteamCMD exited with status {}",
             status
                 .code()
                 .map(|code| code.to_string())
                 .unwrap_or_else(|| "terminated".to_string())
-        ));
+        ))
+        .into());
     }
 
     Ok(combined)
@@ -382,7 +496,7 @@ fn is_usable_workshop_dir(path: &Path) -> bool {
 fn wait_for_process_with_output(
     child: &mut std::process::Child,
     rx: &mpsc::Receiver<String>,
-    logger: Option<&dyn Fn(String)>,
+    logger: Option<&dyn Fn(Level, String)>,
     combined: &mut String,
 ) -> Result<ExitStatus> {
     loop {
@@ -423,8 +537,8 @@ fn spawn_output_reader<R: Read + Send + 'static>(
     })
 }
 
-fn append_output_line(logger: Option<&dyn Fn(String)>, combined: &mut String, line: String) {
-    log(logger, line.clone());
+fn append_output_line(logger: Option<&dyn Fn(Level, String)>, combined: &mut String, line: String) {
+    log(logger, Level::Info, line.clone());
     combined.push_str(&line);
     combined.push('\n');
 }
@@ -446,11 +560,11 @@ pub fn find_steamcmd() -> Option<PathBuf> {
     steamcmd.exists().then_some(steamcmd)
 }
 
-pub fn prepare_steamcmd(logger: Option<&dyn Fn(String)>) -> Result<PathBuf> {
+pub fn prepare_steamcmd(logger: Option<&dyn Fn(Level, String)>) -> Result<PathBuf> {
     ensure_steamcmd(logger)
 }
 
-fn ensure_steamcmd(logger: Option<&dyn Fn(String)>) -> Result<PathBuf> {
+fn ensure_steamcmd(logger: Option<&dyn Fn(Level, String)>) -> Result<PathBuf> {
     if let Some(path) = find_steamcmd() {
         return Ok(path);
     }
@@ -459,22 +573,22 @@ fn ensure_steamcmd(logger: Option<&dyn Fn(String)>) -> Result<PathBuf> {
     let install_dir = steamcmd
         .parent()
         .context("SteamCMD install path has no parent directory")?;
-    fs::create_dir_all(&install_dir)?;
+    fs::create_dir_all(install_dir)?;
     log(
         logger,
+        Level::Info,
         format!("Downloading SteamCMD to {}...", install_dir.display()),
     );
 
-    let bytes = Client::builder()
-        .user_agent("isaac_mod_manager")
-        .build()?
-        .get(STEAMCMD_ZIP_URL)
-        .send()?
-        .error_for_status()?
-        .bytes()?;
+    let zip_path = download_steamcmd_zip(install_dir, logger)?;
+    let result = extract_steamcmd(&zip_path, install_dir);
+    let _ = fs::remove_file(&zip_path);
+    result
+}
 
-    let cursor = Cursor::new(bytes);
-    let mut archive = ZipArchive::new(cursor)?;
+fn extract_steamcmd(zip_path: &Path, install_dir: &Path) -> Result<PathBuf> {
+    let mut archive = ZipArchive::new(fs::File::open(zip_path)?)
+        .context("Downloaded SteamCMD archive is not a valid zip")?;
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
         let Some(file_name) = Path::new(file.name()).file_name() else {
@@ -482,8 +596,19 @@ fn ensure_steamcmd(logger: Option<&dyn Fn(String)>) -> Result<PathBuf> {
         };
         if file_name == "steamcmd.exe" {
             let output_path = install_dir.join(file_name);
-            let mut output = fs::File::create(&output_path)?;
-            std::io::copy(&mut file, &mut output)?;
+            let temp_path = output_path.with_extension("exe.tmp");
+            let mut output = fs::File::create(&temp_path)?;
+            let written = io::copy(&mut file, &mut output)?;
+            drop(output);
+            if written != file.size() {
+                let _ = fs::remove_file(&temp_path);
+                return Err(anyhow::anyhow!(
+                    "steamcmd.exe was truncated while extracting ({} of {} bytes)",
+                    written,
+                    file.size()
+                ));
+            }
+            fs::rename(&temp_path, &output_path)?;
             return Ok(output_path);
         }
     }
@@ -493,6 +618,207 @@ fn ensure_steamcmd(logger: Option<&dyn Fn(String)>) -> Result<PathBuf> {
     ))
 }
 
+// A crash while extracting can leave a partial temp file or, from older builds, an empty
+// steamcmd.exe that find_steamcmd would otherwise reuse.
+pub fn clean_stale_steamcmd_downloads() {
+    let Ok(steamcmd) = local_steamcmd_path() else {
+        return;
+    };
+    if fs::metadata(&steamcmd).is_ok_and(|metadata| metadata.len() == 0) {
+        let _ = fs::remove_file(&steamcmd);
+    }
+
+    let Some(Ok(entries)) = steamcmd.parent().map(fs::read_dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "tmp") {
+            continue;
+        }
+        let is_stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_none_or(|age| age >= STALE_DOWNLOAD_AGE);
+        if is_stale {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn download_steamcmd_zip(
+    install_dir: &Path,
+    logger: Option<&dyn Fn(Level, String)>,
+) -> Result<PathBuf> {
+    let client = http_client()?;
+    let zip_path = install_dir.join("steamcmd.zip.tmp");
+    let _ = fs::remove_file(&zip_path);
+    let mut last_error = None;
+    for url in steamcmd_zip_urls() {
+        match download_with_resume(client, &url, &zip_path, logger)
+            .and_then(|()| verify_steamcmd_zip(&zip_path))
+        {
+            Ok(()) => return Ok(zip_path),
+            Err(e) => {
+                log(
+                    logger,
+                    Level::Warn,
+                    format!("SteamCMD download failed from {}: {:#}", url, e),
+                );
+                let _ = fs::remove_file(&zip_path);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error
+        .unwrap_or_else(|| anyhow::anyhow!("No SteamCMD download URL configured"))
+        .context("Failed to download SteamCMD from every configured URL"))
+}
+
+// A corrupt download from one mirror falls through to the next URL instead of failing
+// inside extraction. STEAMCMD_ZIP_SHA256 optionally pins the expected archive hash.
+fn verify_steamcmd_zip(zip_path: &Path) -> Result<()> {
+    let integrity_error =
+        |detail: String| anyhow::anyhow!("Downloaded archive failed integrity check: {}", detail);
+    if let Ok(expected) = env::var(STEAMCMD_ZIP_SHA256_ENV) {
+        let mut hasher = Sha256::new();
+        io::copy(&mut fs::File::open(zip_path)?, &mut hasher)?;
+        let actual = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return Err(integrity_error(format!("SHA-256 was {}", actual)));
+        }
+    }
+
+    let mut archive =
+        ZipArchive::new(fs::File::open(zip_path)?).map_err(|e| integrity_error(e.to_string()))?;
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| integrity_error(e.to_string()))?;
+        io::copy(&mut file, &mut io::sink()).map_err(|e| integrity_error(e.to_string()))?;
+    }
+    Ok(())
+}
+
+// An interrupted transfer continues from the bytes already on disk with a Range request.
+// A 4xx answer or a local file error will not change on retry, so the next mirror is tried instead.
+fn download_with_resume(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    logger: Option<&dyn Fn(Level, String)>,
+) -> Result<()> {
+    let mut attempt = 1;
+    loop {
+        match download_remaining_bytes(client, url, path, logger) {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < ZIP_RESUME_ATTEMPTS && !is_permanent_download_error(&e) => {
+                log(
+                    logger,
+                    Level::Warn,
+                    format!(
+                        "Download interrupted ({:#}); resuming ({}/{})...",
+                        e, attempt, ZIP_RESUME_ATTEMPTS
+                    ),
+                );
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn download_remaining_bytes(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    logger: Option<&dyn Fn(Level, String)>,
+) -> Result<()> {
+    let received = fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    let mut request = client.get(url).timeout(request_timeout());
+    if received > 0 {
+        request = request.header(RANGE, format!("bytes={}-", received));
+    }
+
+    let response = send_with_retry(request, logger).map_err(describe_request_error)?;
+    if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        fs::remove_file(path)?;
+        anyhow::bail!("Server rejected the resume range");
+    }
+    let mut response = match response.error_for_status() {
+        Ok(response) => response,
+        Err(e) if e.status().is_some_and(|status| status.is_client_error()) => {
+            return Err(PatcherError::Network(e).into());
+        }
+        Err(e) => return Err(describe_request_error(e)),
+    };
+
+    let resumed = received > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+    if received > 0 && !resumed {
+        log(
+            logger,
+            Level::Info,
+            "Server does not support resuming; restarting download.".to_string(),
+        );
+    }
+    let expected_size =
+        response
+            .content_length()
+            .map(|length| if resumed { length + received } else { length });
+
+    let file = if resumed {
+        fs::OpenOptions::new().append(true).open(path)
+    } else {
+        fs::File::create(path)
+    };
+    let mut file = file.map_err(PatcherError::Io)?;
+    io::copy(&mut response, &mut file)?;
+    drop(file);
+
+    let size = fs::metadata(path)?.len();
+    match expected_size {
+        Some(expected_size) if size != expected_size => Err(anyhow::anyhow!(
+            "Downloaded {} of {} bytes",
+            size,
+            expected_size
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn is_permanent_download_error(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<PatcherError>() {
+        Some(PatcherError::Network(e)) => e.status().is_some_and(|status| status.is_client_error()),
+        Some(PatcherError::Io(_)) => true,
+        _ => false,
+    }
+}
+
+// Extra URLs come from STEAMCMD_ZIP_MIRRORS (separated by ';') and are tried after the
+// official CDN, for networks where it is blocked.
+fn steamcmd_zip_urls() -> Vec<String> {
+    let mut urls = vec![STEAMCMD_ZIP_URL.to_string()];
+    if let Ok(mirrors) = env::var(STEAMCMD_ZIP_MIRRORS_ENV) {
+        urls.extend(
+            mirrors
+                .split(';')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(ToOwned::to_owned),
+        );
+    }
+    urls
+}
+
 fn find_steamcmd_in_path() -> Option<PathBuf> {
     let paths = env::var_os("PATH")?;
     for path in env::split_paths(&paths) {
@@ -508,16 +834,6 @@ fn local_steamcmd_path() -> Result<PathBuf> {
     Ok(local_app_dir()?.join("steamcmd").join("steamcmd.exe"))
 }
 
-fn local_app_dir() -> Result<PathBuf> {
-    if let Some(local_app_data) = env::var_os("LOCALAPPDATA") {
-        return Ok(PathBuf::from(local_app_data)
-            .join("Ba-koD")
-            .join("isaac_mod_manager"));
-    }
-
-    Ok(env::current_dir()?.join(".isaac_mod_manager"))
-}
-
 fn decode_process_output(bytes: &[u8]) -> String {
     if bytes.is_empty() {
         return String::new();
@@ -531,10 +847,3 @@ fn decode_process_output(bytes: &[u8]) -> String {
         }
     }
 }
-
-fn log(logger: Option<&dyn Fn(String)>, msg: String) {
-    if let Some(f) = logger {
-        f(msg.clone());
-    }
-    println!("{}", msg);
-}